@@ -0,0 +1,696 @@
+use std::sync::{Arc, Mutex};
+
+use pulse::callbacks::ListResult;
+use pulse::context::Context;
+use pulse::context::subscribe::{Facility, InterestMaskSet, Operation};
+use pulse::mainloop::standard::Mainloop;
+use pulse::volume::ChannelVolumes;
+
+use crate::{App, CardEntry, ModuleEntry, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+
+/// Everything done with the sound server. Implemented by `PulseBackend` and, for tests, by
+/// `FakeBackend`, which only records the calls.
+pub trait Backend {
+    /// State of the connection, the other operations need it to be `Ready`.
+    fn state(&self) -> pulse::context::State;
+    /// Start connecting to a server, the default one for `None`. Returns false if that failed at
+    /// once.
+    fn connect(&mut self, server: Option<&str>) -> bool;
+    fn disconnect(&mut self);
+    /// List all entries and the server info and subscribe to their changes. The answers update
+    /// the `App` as they arrive.
+    fn subscribe(&mut self);
+
+    fn set_sink_volume(&mut self, index: u32, volume: &ChannelVolumes);
+    fn set_sink_mute(&mut self, index: u32, mute: bool);
+    fn set_sink_port(&mut self, index: u32, port: &str);
+    fn set_default_sink(&mut self, name: &str);
+
+    fn set_source_volume(&mut self, index: u32, volume: &ChannelVolumes);
+    fn set_source_mute(&mut self, index: u32, mute: bool);
+    fn set_source_port(&mut self, index: u32, port: &str);
+    fn set_default_source(&mut self, name: &str);
+
+    fn set_sink_input_volume(&mut self, index: u32, volume: &ChannelVolumes);
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool);
+    fn move_sink_input(&mut self, index: u32, sink: u32);
+    fn kill_sink_input(&mut self, index: u32);
+
+    fn set_source_output_volume(&mut self, index: u32, volume: &ChannelVolumes);
+    fn set_source_output_mute(&mut self, index: u32, mute: bool);
+    fn move_source_output(&mut self, index: u32, source: u32);
+    fn kill_source_output(&mut self, index: u32);
+
+    fn set_card_profile(&mut self, index: u32, profile: &str);
+    fn unload_module(&mut self, index: u32);
+    fn load_module(&mut self, name: &str, argument: &str);
+}
+
+/// The libpulse implementation. Each operation reports its outcome in the status line once the
+/// server answered, the callbacks run after the caller released the locks.
+pub struct PulseBackend<'a> {
+    pub context:  &'a mut Context,
+    pub shared:   Arc<Mutex<Context>>,
+    pub app:      Arc<Mutex<App>>,
+    /// For the new context of a connection attempt.
+    pub mainloop: &'a Mainloop,
+}
+
+fn percent(volume: &ChannelVolumes) -> u32 {
+    return (volume.avg().0 as f64 / pulse::volume::Volume::NORMAL.0 as f64 * 100f64).round() as u32;
+}
+
+fn sink_name(app: &App, index: u32) -> String {
+    return app.sink_list.get(index).map(|x| x.display_name()).unwrap_or_else(|| format!("sink #{}", index));
+}
+
+fn source_name(app: &App, index: u32) -> String {
+    return app.source_list.get(index).map(|x| x.display_name()).unwrap_or_else(|| format!("source #{}", index));
+}
+
+fn sink_input_name(app: &App, index: u32) -> String {
+    return app.sink_input_list.get(index).map(|x| x.display_name()).unwrap_or_else(|| format!("stream #{}", index));
+}
+
+fn source_output_name(app: &App, index: u32) -> String {
+    return app.source_output_list.get(index).map(|x| x.display_name()).unwrap_or_else(|| format!("stream #{}", index));
+}
+
+fn card_name(app: &App, index: u32) -> String {
+    return app.card_list.get(index).map(|x| x.display_name()).unwrap_or_else(|| format!("card #{}", index));
+}
+
+fn mute_label(mute: bool) -> &'static str {
+    return if mute { "muted" } else { "unmuted" };
+}
+
+/// Request the default sink and source and the name of the server.
+fn request_server_info(app: &Arc<Mutex<App>>, context: &Context) {
+    context.introspect().get_server_info({
+        let app = Arc::clone(app);
+        move |info| {
+            let mut app = app.lock().unwrap();
+            app.default_sink_name = info.default_sink_name.as_ref().map(|x| String::from(x.as_ref()));
+            app.default_source_name = info.default_source_name.as_ref().map(|x| String::from(x.as_ref()));
+            app.server_label = match (&info.server_name, &info.server_version) {
+                (Some(name), Some(version)) => Some(format!("{} {}", name, version)),
+                (Some(name), None)          => Some(String::from(name.as_ref())),
+                _                           => None,
+            };
+            app.redraw = true;
+        }
+    });
+}
+
+impl PulseBackend<'_> {
+    /// Build the callback of an operation. `describe` names what was done, with the entries looked
+    /// up once the answer arrives.
+    fn report<F>(&self, describe: F) -> impl FnMut(bool) + 'static
+        where F: Fn(&App) -> String + 'static {
+        let app = Arc::clone(&self.app);
+        let context = Arc::clone(&self.shared);
+        return move |success| {
+            let error = if success { None } else { Some(context.lock().unwrap().errno()) };
+            let mut app = app.lock().unwrap();
+            let description = describe(&app);
+            match error {
+                None        => app.status.info(description),
+                Some(error) => app.status.error(format!("{} failed: {}", description, error)),
+            }
+            app.redraw = true;
+        };
+    }
+}
+
+impl Backend for PulseBackend<'_> {
+    fn state(&self) -> pulse::context::State {
+        return self.context.get_state();
+    }
+
+    fn connect(&mut self, server: Option<&str>) -> bool {
+        // a context can only connect once
+        *self.context = crate::create_context(self.mainloop);
+        return self.context.connect(server, pulse::context::FlagSet::NOFLAGS, None).is_ok();
+    }
+
+    fn disconnect(&mut self) {
+        self.context.disconnect();
+    }
+
+    fn subscribe(&mut self) {
+        let app = &self.app;
+
+        // Retrieve initial sinkinput list
+        self.context.introspect().get_sink_input_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+
+                match listres {
+                    ListResult::Item(i) => {
+                        let mut app = app.lock().unwrap();
+                        app.sink_input_list.update(i.index, SinkInputEntry::from(i));
+                                        // println!("{}", i.proplist.to_string().unwrap());
+                    },
+                    ListResult::End |
+                    ListResult::Error => {},
+                }
+
+            }
+        });
+
+        // Retrieve initial sinkinput list
+        self.context.introspect().get_source_output_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+
+                match listres {
+                    ListResult::Item(i) => {
+                        if crate::is_own_stream(&i.proplist) {
+                            return;
+                        }
+                        let mut app = app.lock().unwrap();
+                        app.source_output_list.update(i.index, SourceOutputEntry::from(i));
+                    },
+                    ListResult::End |
+                    ListResult::Error => {},
+                }
+
+            }
+        });
+
+        // Retrieve initial sink list
+        self.context.introspect().get_sink_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+
+                match listres {
+                    ListResult::Item(i) => {
+                        let mut app = app.lock().unwrap();
+                        app.sink_list.update(i.index, SinkEntry::from(i));
+                                        // println!("{}", i.proplist.to_string().unwrap());
+                    },
+                    ListResult::End => {
+                        let mut app = app.lock().unwrap();
+                        let app = &mut *app;
+                        if let Some(name) = app.saved_selection.sink.take() {
+                            if let Some(index) = app.sink_list.find_key(|x| x.name == name) {
+                                app.sink_list.select(index);
+                            }
+                        }
+                    }
+                    ListResult::Error => {},
+                }
+
+            }
+        });
+
+        // Retrieve initial source list
+        self.context.introspect().get_source_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+
+                match listres {
+                    ListResult::Item(i) => {
+                        let mut app = app.lock().unwrap();
+                        app.source_list.update(i.index, SourceEntry::from(i));
+                    },
+                    ListResult::End => {
+                        let mut app = app.lock().unwrap();
+                        let app = &mut *app;
+                        if let Some(name) = app.saved_selection.source.take() {
+                            if let Some(index) = app.source_list.find_key(|x| x.name == name) {
+                                app.source_list.select(index);
+                            }
+                        }
+                    }
+                    ListResult::Error => {},
+                }
+
+            }
+        });
+
+        // Retrieve initial card list
+        self.context.introspect().get_card_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+
+                match listres {
+                    ListResult::Item(i) => {
+                        let mut app = app.lock().unwrap();
+                        app.card_list.update(i.index, CardEntry::from(i));
+                        // let app = app.lock().unwrap();
+                        // println!("CARD: ");
+                        // println!("{}", i.proplist.to_string().unwrap());
+                        // for p in i.profiles.iter() {
+                        //     println!("{} {} {} {}", p.name.as_ref().unwrap(), p.priority, p.description.as_ref().unwrap(), p.available);
+                        // }
+                        // for port in i.ports.iter() {
+                        //     println!("{} {} {}", port.name.as_ref().unwrap(), port.priority, port.description.as_ref().unwrap());
+                        //     println!("{}", port.proplist.to_string().unwrap());
+                        // }
+                    },
+                    ListResult::End => {
+                        let mut app = app.lock().unwrap();
+                        let app = &mut *app;
+                        if let Some(name) = app.saved_selection.card.take() {
+                            if let Some(index) = app.card_list.find_key(|x| x.name == name) {
+                                app.card_list.select(index);
+                            }
+                        }
+                    }
+                    ListResult::Error => {},
+                }
+
+            }
+        });
+
+        // Retrieve initial module list
+        self.context.introspect().get_module_info_list({
+            let app = Arc::clone(app);
+            move |listres| {
+                if let ListResult::Item(i) = listres {
+                    let mut app = app.lock().unwrap();
+                    app.module_list.update(i.index, ModuleEntry::from(i));
+                }
+            }
+        });
+
+        // Retrieve initial default sink and source
+        request_server_info(app, self.context);
+
+        // Subscribe to change events
+        let interest = InterestMaskSet::SERVER |
+            InterestMaskSet::SINK_INPUT |
+            InterestMaskSet::SINK |
+            InterestMaskSet::SOURCE_OUTPUT |
+            InterestMaskSet::SOURCE |
+            InterestMaskSet::CARD |
+            InterestMaskSet::MODULE;
+        self.context.subscribe(
+            interest,
+            |_| {}
+        );
+
+        self.context.set_subscribe_callback(Some(Box::new({
+            let app = Arc::clone(app);
+            let context = Arc::clone(&self.shared);
+            move |facility, operation, index| {
+
+                let app = Arc::clone(&app);
+                let (facility, operation) = match (facility, operation) {
+                    (Some(facility), Some(operation)) => (facility, operation),
+                    _ => {
+                        crate::log::warn(&format!("ignoring unknown subscription event for object {}", index));
+                        return;
+                    }
+                };
+                match facility {
+
+                    Facility::Server => {
+                        request_server_info(&app, &context.lock().unwrap());
+                    }
+
+                    Facility::SinkInput => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_sink_input_info(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            let mut app = app.lock().unwrap();
+                                            app.sink_input_list.update(i.index, SinkInputEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.sink_input_list.remove(index);
+                            }
+                        }
+                    }
+
+                    Facility::SourceOutput => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_source_output_info(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            if crate::is_own_stream(&i.proplist) {
+                                                return;
+                                            }
+                                            let mut app = app.lock().unwrap();
+                                            app.source_output_list.update(i.index, SourceOutputEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.source_output_list.remove(index);
+                            }
+                        }
+                    }
+
+                    Facility::Sink => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_sink_info_by_index(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            let mut app = app.lock().unwrap();
+                                            app.sink_list.update(i.index, SinkEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.sink_list.remove(index);
+                            }
+                        }
+                    }
+
+                    Facility::Source => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_source_info_by_index(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            let mut app = app.lock().unwrap();
+                                            app.source_list.update(i.index, SourceEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.source_list.remove(index);
+                            }
+                        }
+                    }
+
+                    Facility::Card => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_card_info_by_index(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            let mut app = app.lock().unwrap();
+                                            app.card_list.update(i.index, CardEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.card_list.remove(index);
+                            }
+                        }
+                    }
+
+                    Facility::Module => {
+                        match operation {
+                            Operation::New | Operation::Changed => {
+                                context.lock().unwrap().introspect().get_module_info(index,
+                                    move |listres| {
+                                        if let ListResult::Item(i) = listres {
+                                            let mut app = app.lock().unwrap();
+                                            app.module_list.update(i.index, ModuleEntry::from(i));
+                                        }
+                                    }
+                                );
+                            },
+                            Operation::Removed => {
+                                let mut app = app.lock().unwrap();
+                                app.module_list.remove(index);
+                            }
+                        }
+                    }
+
+                    _ => {}
+                }
+
+            }
+        })));
+    }
+
+    fn set_sink_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let percent = percent(volume);
+        let callback = self.report(move |app| format!("Volume of {} set to {}%", sink_name(app, index), percent));
+        self.context.introspect().set_sink_volume_by_index(index, volume, Some(Box::new(callback)));
+    }
+
+    fn set_sink_mute(&mut self, index: u32, mute: bool) {
+        let callback = self.report(move |app| format!("{} {}", sink_name(app, index), mute_label(mute)));
+        self.context.introspect().set_sink_mute_by_index(index, mute, Some(Box::new(callback)));
+    }
+
+    fn set_sink_port(&mut self, index: u32, port: &str) {
+        let port_name = String::from(port);
+        let callback = self.report(move |app| format!("Port of {} set to {}", sink_name(app, index), port_name));
+        self.context.introspect().set_sink_port_by_index(index, port, Some(Box::new(callback)));
+    }
+
+    fn set_default_sink(&mut self, name: &str) {
+        let sink = String::from(name);
+        let callback = self.report(move |_| format!("Default sink set to {}", sink));
+        self.context.set_default_sink(name, callback);
+    }
+
+    fn set_source_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let percent = percent(volume);
+        let callback = self.report(move |app| format!("Volume of {} set to {}%", source_name(app, index), percent));
+        self.context.introspect().set_source_volume_by_index(index, volume, Some(Box::new(callback)));
+    }
+
+    fn set_source_mute(&mut self, index: u32, mute: bool) {
+        let callback = self.report(move |app| format!("{} {}", source_name(app, index), mute_label(mute)));
+        self.context.introspect().set_source_mute_by_index(index, mute, Some(Box::new(callback)));
+    }
+
+    fn set_source_port(&mut self, index: u32, port: &str) {
+        let port_name = String::from(port);
+        let callback = self.report(move |app| format!("Port of {} set to {}", source_name(app, index), port_name));
+        self.context.introspect().set_source_port_by_index(index, port, Some(Box::new(callback)));
+    }
+
+    fn set_default_source(&mut self, name: &str) {
+        let source = String::from(name);
+        let callback = self.report(move |_| format!("Default source set to {}", source));
+        self.context.set_default_source(name, callback);
+    }
+
+    fn set_sink_input_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let percent = percent(volume);
+        let callback = self.report(move |app| format!("Volume of {} set to {}%", sink_input_name(app, index), percent));
+        self.context.introspect().set_sink_input_volume(index, volume, Some(Box::new(callback)));
+    }
+
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool) {
+        let callback = self.report(move |app| format!("{} {}", sink_input_name(app, index), mute_label(mute)));
+        self.context.introspect().set_sink_input_mute(index, mute, Some(Box::new(callback)));
+    }
+
+    fn move_sink_input(&mut self, index: u32, sink: u32) {
+        let callback = self.report(move |app| format!("Moved {} to {}", sink_input_name(app, index), sink_name(app, sink)));
+        self.context.introspect().move_sink_input_by_index(index, sink, Some(Box::new(callback)));
+    }
+
+    fn kill_sink_input(&mut self, index: u32) {
+        let callback = self.report(move |app| format!("Killed {}", sink_input_name(app, index)));
+        self.context.introspect().kill_sink_input(index, callback);
+    }
+
+    fn set_source_output_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let percent = percent(volume);
+        let callback = self.report(move |app| format!("Volume of {} set to {}%", source_output_name(app, index), percent));
+        self.context.introspect().set_source_output_volume(index, volume, Some(Box::new(callback)));
+    }
+
+    fn set_source_output_mute(&mut self, index: u32, mute: bool) {
+        let callback = self.report(move |app| format!("{} {}", source_output_name(app, index), mute_label(mute)));
+        self.context.introspect().set_source_output_mute(index, mute, Some(Box::new(callback)));
+    }
+
+    fn move_source_output(&mut self, index: u32, source: u32) {
+        let callback = self.report(move |app| format!("Moved {} to {}", source_output_name(app, index), source_name(app, source)));
+        self.context.introspect().move_source_output_by_index(index, source, Some(Box::new(callback)));
+    }
+
+    fn kill_source_output(&mut self, index: u32) {
+        let callback = self.report(move |app| format!("Killed {}", source_output_name(app, index)));
+        self.context.introspect().kill_source_output(index, callback);
+    }
+
+    fn set_card_profile(&mut self, index: u32, profile: &str) {
+        let profile_name = String::from(profile);
+        let callback = self.report(move |app| {
+            let profile = app.card_list.get(index)
+                .and_then(|card| card.profiles.iter().find(|x| x.name == profile_name))
+                .map(|x| x.display_name().to_string())
+                .unwrap_or_else(|| profile_name.clone());
+            format!("Profile of {} set to {}", card_name(app, index), profile)
+        });
+        self.context.introspect().set_card_profile_by_index(index, profile, Some(Box::new(callback)));
+    }
+
+    fn unload_module(&mut self, index: u32) {
+        let callback = self.report(move |_| format!("Unloaded module #{}", index));
+        self.context.introspect().unload_module(index, callback);
+    }
+
+    fn load_module(&mut self, name: &str, argument: &str) {
+        // the answer is the index of the new module, or an invalid one on failure
+        let mut callback = self.report({
+            let name = String::from(name);
+            move |_| format!("Loaded {}", name)
+        });
+        self.context.introspect().load_module(name, argument, move |index| {
+            callback(index != pulse::def::INVALID_INDEX);
+        });
+    }
+}
+
+/// A call made on the `FakeBackend`. Volumes are recorded as their average, in percent.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Call {
+    Connect(Option<String>),
+    Disconnect,
+    Subscribe,
+    SetSinkVolume(u32, u32),
+    SetSinkMute(u32, bool),
+    SetSinkPort(u32, String),
+    SetDefaultSink(String),
+    SetSourceVolume(u32, u32),
+    SetSourceMute(u32, bool),
+    SetSourcePort(u32, String),
+    SetDefaultSource(String),
+    SetSinkInputVolume(u32, u32),
+    SetSinkInputMute(u32, bool),
+    MoveSinkInput(u32, u32),
+    KillSinkInput(u32),
+    SetSourceOutputVolume(u32, u32),
+    SetSourceOutputMute(u32, bool),
+    MoveSourceOutput(u32, u32),
+    KillSourceOutput(u32),
+    SetCardProfile(u32, String),
+    UnloadModule(u32),
+    LoadModule(String, String),
+}
+
+/// In-memory backend recording the calls made, in order.
+#[cfg(test)]
+pub struct FakeBackend {
+    pub calls: Vec<Call>,
+    /// The connection state reported, set by tests to what the server would do.
+    pub state: pulse::context::State,
+}
+
+#[cfg(test)]
+impl Default for FakeBackend {
+    fn default() -> FakeBackend {
+        FakeBackend {
+            calls: Vec::new(),
+            state: pulse::context::State::Ready,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Backend for FakeBackend {
+    fn state(&self) -> pulse::context::State {
+        return self.state;
+    }
+
+    fn connect(&mut self, server: Option<&str>) -> bool {
+        self.calls.push(Call::Connect(server.map(String::from)));
+        self.state = pulse::context::State::Connecting;
+        return true;
+    }
+
+    fn disconnect(&mut self) {
+        self.calls.push(Call::Disconnect);
+        self.state = pulse::context::State::Terminated;
+    }
+
+    fn subscribe(&mut self) {
+        self.calls.push(Call::Subscribe);
+    }
+
+    fn set_sink_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        self.calls.push(Call::SetSinkVolume(index, percent(volume)));
+    }
+
+    fn set_sink_mute(&mut self, index: u32, mute: bool) {
+        self.calls.push(Call::SetSinkMute(index, mute));
+    }
+
+    fn set_sink_port(&mut self, index: u32, port: &str) {
+        self.calls.push(Call::SetSinkPort(index, String::from(port)));
+    }
+
+    fn set_default_sink(&mut self, name: &str) {
+        self.calls.push(Call::SetDefaultSink(String::from(name)));
+    }
+
+    fn set_source_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        self.calls.push(Call::SetSourceVolume(index, percent(volume)));
+    }
+
+    fn set_source_mute(&mut self, index: u32, mute: bool) {
+        self.calls.push(Call::SetSourceMute(index, mute));
+    }
+
+    fn set_source_port(&mut self, index: u32, port: &str) {
+        self.calls.push(Call::SetSourcePort(index, String::from(port)));
+    }
+
+    fn set_default_source(&mut self, name: &str) {
+        self.calls.push(Call::SetDefaultSource(String::from(name)));
+    }
+
+    fn set_sink_input_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        self.calls.push(Call::SetSinkInputVolume(index, percent(volume)));
+    }
+
+    fn set_sink_input_mute(&mut self, index: u32, mute: bool) {
+        self.calls.push(Call::SetSinkInputMute(index, mute));
+    }
+
+    fn move_sink_input(&mut self, index: u32, sink: u32) {
+        self.calls.push(Call::MoveSinkInput(index, sink));
+    }
+
+    fn kill_sink_input(&mut self, index: u32) {
+        self.calls.push(Call::KillSinkInput(index));
+    }
+
+    fn set_source_output_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        self.calls.push(Call::SetSourceOutputVolume(index, percent(volume)));
+    }
+
+    fn set_source_output_mute(&mut self, index: u32, mute: bool) {
+        self.calls.push(Call::SetSourceOutputMute(index, mute));
+    }
+
+    fn move_source_output(&mut self, index: u32, source: u32) {
+        self.calls.push(Call::MoveSourceOutput(index, source));
+    }
+
+    fn kill_source_output(&mut self, index: u32) {
+        self.calls.push(Call::KillSourceOutput(index));
+    }
+
+    fn set_card_profile(&mut self, index: u32, profile: &str) {
+        self.calls.push(Call::SetCardProfile(index, String::from(profile)));
+    }
+
+    fn unload_module(&mut self, index: u32) {
+        self.calls.push(Call::UnloadModule(index));
+    }
+
+    fn load_module(&mut self, name: &str, argument: &str) {
+        self.calls.push(Call::LoadModule(String::from(name), String::from(argument)));
+    }
+}
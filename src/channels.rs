@@ -0,0 +1,183 @@
+use pulse::channelmap::{Map, Position};
+use pulse::volume::{ChannelVolumes, Volume};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumeChange {
+    Increase(u32),
+    Decrease(u32),
+    Set(u32),
+}
+
+/// Per-view state of the per-channel volume mode.
+///
+/// When expanded, the selected entry is drawn with one gauge per channel. While the channels are
+/// locked, volume changes apply to all channels keeping their proportions, otherwise only to the
+/// selected channel.
+pub struct ChannelState {
+    expanded: bool,
+    locked:   bool,
+    selected: usize,
+}
+
+impl Default for ChannelState {
+    fn default() -> ChannelState {
+        ChannelState {
+            expanded: false,
+            locked:   true,
+            selected: 0,
+        }
+    }
+}
+
+impl ChannelState {
+    pub fn is_expanded(&self) -> bool {
+        return self.expanded;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        return self.locked;
+    }
+
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    pub fn toggle_locked(&mut self) {
+        self.locked = !self.locked;
+    }
+
+    pub fn select_next(&mut self, channels: u8) {
+        if self.selected + 1 < channels as usize {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+
+    /// The selected channel, clamped to the given number of channels, as entries may differ in
+    /// their channel count.
+    pub fn selected(&self, channels: u8) -> usize {
+        return self.selected.min((channels as usize).saturating_sub(1));
+    }
+
+    /// The channel a volume change applies to, `None` meaning all of them.
+    pub fn target_channel(&self, channels: u8) -> Option<usize> {
+        if self.expanded && !self.locked && channels > 0 {
+            return Some(self.selected(channels));
+        }
+        return None;
+    }
+
+    /// Number of rows needed to draw an entry.
+    pub fn entry_height(&self, is_selected: bool, channels: u8) -> u16 {
+        if self.expanded && is_selected && channels > 0 {
+            return 2 + channels as u16;
+        }
+        return 3;
+    }
+
+    /// Apply a volume change, never raising a volume above `max`. Volumes which are already above
+    /// it, e.g. as set by another client, are left as they are by increases.
+    pub fn change_volume(&self, volume: &ChannelVolumes, change: VolumeChange, max: u32) -> ChannelVolumes {
+        let mut new_vol = volume.clone();
+
+        match self.target_channel(volume.len()) {
+            None => match change {
+                VolumeChange::Increase(step) => {
+                    if new_vol.max().0 < max {
+                        new_vol.inc_clamp(Volume(step), Volume(max));
+                    }
+                }
+                VolumeChange::Decrease(step) => { new_vol.decrease(Volume(step)); }
+                VolumeChange::Set(value)     => { new_vol.set(new_vol.len(), Volume(value.min(max))); }
+            },
+            Some(channel) => {
+                let v = &mut new_vol.get_mut()[channel];
+                v.0 = match change {
+                    VolumeChange::Increase(step) => v.0.max(v.0.saturating_add(step).min(max)),
+                    VolumeChange::Decrease(step) => v.0.saturating_sub(step),
+                    VolumeChange::Set(value)     => value.min(max),
+                };
+            }
+        }
+
+        return new_vol;
+    }
+}
+
+pub fn channel_name(map: &Map, channel: usize) -> String {
+    return map.get().get(channel)
+        .and_then(|position| Position::to_string(*position))
+        .map(|name| String::from(name.as_ref()))
+        .unwrap_or_else(|| format!("channel {}", channel));
+}
+
+pub const BALANCE_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy)]
+pub enum BalanceKind {
+    Balance,
+    Fade,
+    Lfe,
+}
+
+impl BalanceKind {
+    pub const ALL: [BalanceKind; 3] = [BalanceKind::Balance, BalanceKind::Fade, BalanceKind::Lfe];
+
+    /// Label and the names of the lower and upper end of the slider.
+    pub fn labels(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            BalanceKind::Balance => ("Balance", "L", "R"),
+            BalanceKind::Fade    => ("Fade", "R", "F"),
+            BalanceKind::Lfe     => ("LFE", "-", "+"),
+        }
+    }
+
+    pub fn applies_to(&self, map: &Map) -> bool {
+        match self {
+            BalanceKind::Balance => map.can_balance(),
+            BalanceKind::Fade    => map.can_fade(),
+            BalanceKind::Lfe     => map.can_lfe_balance(),
+        }
+    }
+
+    /// The current value between `-1.0` and `1.0`, or `None` if the channel map has no such
+    /// control.
+    pub fn get(&self, volume: &ChannelVolumes, map: &Map) -> Option<f32> {
+        if !self.applies_to(map) {
+            return None;
+        }
+        match self {
+            BalanceKind::Balance => Some(volume.get_balance(map)),
+            BalanceKind::Fade    => Some(volume.get_fade(map)),
+            BalanceKind::Lfe     => Some(volume.get_lfe_balance(map)),
+        }
+    }
+
+    pub fn set(&self, volume: &ChannelVolumes, map: &Map, value: f32) -> Option<ChannelVolumes> {
+        if !self.applies_to(map) {
+            return None;
+        }
+        let mut new_vol = volume.clone();
+        let value = value.clamp(-1.0, 1.0);
+        match self {
+            BalanceKind::Balance => new_vol.set_balance(map, value)?,
+            BalanceKind::Fade    => new_vol.set_fade(map, value)?,
+            BalanceKind::Lfe     => new_vol.set_lfe_balance(map, value)?,
+        };
+        return Some(new_vol);
+    }
+
+    pub fn change(&self, volume: &ChannelVolumes, map: &Map, delta: f32) -> Option<ChannelVolumes> {
+        let value = self.get(volume, map)?;
+        return self.set(volume, map, value + delta);
+    }
+}
+
+pub fn has_balance_controls(map: &Map) -> bool {
+    return BalanceKind::ALL.iter().any(|kind| kind.applies_to(map));
+}
@@ -0,0 +1,721 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+
+use pulse::callbacks::ListResult;
+use pulse::context::Context;
+use pulse::context::subscribe::{Facility, InterestMaskSet, Operation as Event};
+use pulse::context::introspect::Introspector;
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::operation::Operation;
+use pulse::volume::ChannelVolumes;
+
+use crate::{CardEntry, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+use crate::channels::{ChannelState, VolumeChange};
+use crate::json::ToJson;
+
+pub const USAGE: &str = "\
+usage: pavucontrolrs [--server <address>] [--max-volume <percent>] [--json] [<command> <args>...]
+
+Without a command, the interactive interface is started. With --json and without a command, the
+whole state is printed as JSON. With --json, list prints JSON too, the commands that change
+something do not support it.
+
+commands:
+    list sinks|sources|sink-inputs|source-outputs|cards
+    watch                               print a JSON line for every change
+    set-volume <sink> <volume>          volume as 40%, +5% or -5%
+    mute-toggle <sink>
+    move-input <application> <sink>    moves all matching streams
+    set-profile <card> <profile>
+
+Entries are given by index, by name or by a part of the name shown in the interface. The default
+sink can be given as @DEFAULT_SINK@.
+
+exit status: 0 on success, 1 if an operation failed, 2 on usage errors, 3 if an argument matches
+no entry or more than one.";
+
+const EXIT_FAILED:   i32 = 1;
+const EXIT_USAGE:    i32 = 2;
+const EXIT_NO_MATCH: i32 = 3;
+
+const DEFAULT_SINK: &str = "@DEFAULT_SINK@";
+
+/// Commands that only print a line of text about what they changed.
+const MODIFYING_COMMANDS: [&str; 4] = ["set-volume", "mute-toggle", "move-input", "set-profile"];
+
+enum CliError {
+    Failed(String),
+    Usage(String),
+    NoMatch(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Failed(_)  => EXIT_FAILED,
+            CliError::Usage(_)   => EXIT_USAGE,
+            CliError::NoMatch(_) => EXIT_NO_MATCH,
+        }
+    }
+}
+
+pub struct Options {
+    pub max_volume: u32,
+    /// Print JSON instead of text.
+    pub json:       bool,
+    pub server:     Option<String>,
+}
+
+/// Run a command given on the command line and return the exit status.
+pub fn run(args: &[String], options: &Options) -> i32 {
+    if let Some(command) = args.first().filter(|x| options.json && MODIFYING_COMMANDS.contains(&x.as_str())) {
+        eprintln!("{} does not support --json\n\n{}", command, USAGE);
+        return EXIT_USAGE;
+    }
+
+    let result = Connection::connect(options.server.as_deref()).and_then(|mut connection| {
+        match args.iter().map(|x| x.as_str()).collect::<Vec<&str>>().as_slice() {
+            [] if options.json             => dump(&mut connection),
+            ["list", kind]                 => list(&mut connection, kind, options.json),
+            ["watch"]                      => watch(&mut connection),
+            ["set-volume", sink, volume]   => set_volume(&mut connection, sink, volume, options.max_volume),
+            ["mute-toggle", sink]          => mute_toggle(&mut connection, sink),
+            ["move-input", stream, sink]   => move_input(&mut connection, stream, sink),
+            ["set-profile", card, profile] => set_profile(&mut connection, card, profile),
+            _ => Err(CliError::Usage(format!("invalid command: {}", args.join(" ")))),
+        }
+    });
+
+    match result {
+        Ok(()) => return 0,
+        Err(e) => {
+            match &e {
+                CliError::Failed(msg)  => eprintln!("{}", msg),
+                CliError::Usage(msg)   => eprintln!("{}\n\n{}", msg, USAGE),
+                CliError::NoMatch(msg) => eprintln!("{}", msg),
+            }
+            return e.exit_code();
+        }
+    }
+}
+
+/// A blocking connection to the server: every request waits for its operation to complete.
+struct Connection {
+    mainloop: Mainloop,
+    context:  Context,
+}
+
+impl Connection {
+    fn connect(server: Option<&str>) -> Result<Connection, CliError> {
+        let mainloop = Mainloop::new().ok_or_else(|| CliError::Failed(String::from("Failed to create mainloop")))?;
+        let mut context = crate::create_context(&mainloop);
+        context.connect(server, pulse::context::FlagSet::NOFLAGS, None)
+            .map_err(|e| CliError::Failed(format!("Failed to connect context: {}", e)))?;
+
+        let mut connection = Connection { mainloop, context };
+        loop {
+            connection.iterate()?;
+            match connection.context.get_state() {
+                pulse::context::State::Ready => { return Ok(connection); },
+                pulse::context::State::Failed |
+                pulse::context::State::Terminated => {
+                    return Err(CliError::Failed(format!("Connection failed: {}", connection.context.errno())));
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn iterate(&mut self) -> Result<(), CliError> {
+        match self.mainloop.iterate(true) {
+            IterateResult::Success(_) => return Ok(()),
+            IterateResult::Quit(_) |
+            IterateResult::Err(_) => return Err(CliError::Failed(String::from("Mainloop iteration failed"))),
+        }
+    }
+
+    fn wait<C: ?Sized>(&mut self, operation: Operation<C>) -> Result<(), CliError> {
+        loop {
+            match operation.get_state() {
+                pulse::operation::State::Done      => return Ok(()),
+                pulse::operation::State::Cancelled => return Err(CliError::Failed(format!("Operation cancelled: {}", self.context.errno()))),
+                pulse::operation::State::Running   => {},
+            }
+            self.iterate()?;
+        }
+    }
+
+    /// Run a request that reports success and wait for it.
+    fn execute<F>(&mut self, request: F) -> Result<(), CliError>
+        where F: FnOnce(&mut Introspector, Box<dyn FnMut(bool)>) -> Operation<dyn FnMut(bool)>
+    {
+        let success = Rc::new(Cell::new(false));
+        let callback = {
+            let success = Rc::clone(&success);
+            Box::new(move |ok| success.set(ok))
+        };
+        let operation = request(&mut self.context.introspect(), callback);
+        self.wait(operation)?;
+        if !success.get() {
+            return Err(CliError::Failed(format!("Operation failed: {}", self.context.errno())));
+        }
+        return Ok(());
+    }
+
+    fn server_info(&mut self) -> Result<ServerInfo, CliError> {
+        let server_info = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_server_info({
+            let server_info = Rc::clone(&server_info);
+            move |info| {
+                let to_string = |x: &Option<std::borrow::Cow<str>>| x.as_ref().map(|x| String::from(x.as_ref()));
+                *server_info.borrow_mut() = Some(ServerInfo {
+                    server_name:         to_string(&info.server_name),
+                    server_version:      to_string(&info.server_version),
+                    default_sink_name:   to_string(&info.default_sink_name),
+                    default_source_name: to_string(&info.default_source_name),
+                });
+            }
+        });
+        self.wait(operation)?;
+        return server_info.take().ok_or_else(|| CliError::Failed(String::from("No server info received")));
+    }
+
+    fn sinks(&mut self) -> Result<Vec<SinkEntry>, CliError> {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let operation = self.context.introspect().get_sink_info_list({
+            let entries = Rc::clone(&entries);
+            move |result| if let ListResult::Item(info) = result { entries.borrow_mut().push(SinkEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entries.take());
+    }
+
+    fn sources(&mut self) -> Result<Vec<SourceEntry>, CliError> {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let operation = self.context.introspect().get_source_info_list({
+            let entries = Rc::clone(&entries);
+            move |result| if let ListResult::Item(info) = result { entries.borrow_mut().push(SourceEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entries.take());
+    }
+
+    fn sink_inputs(&mut self) -> Result<Vec<SinkInputEntry>, CliError> {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let operation = self.context.introspect().get_sink_input_info_list({
+            let entries = Rc::clone(&entries);
+            move |result| if let ListResult::Item(info) = result { entries.borrow_mut().push(SinkInputEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entries.take());
+    }
+
+    fn source_outputs(&mut self) -> Result<Vec<SourceOutputEntry>, CliError> {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let operation = self.context.introspect().get_source_output_info_list({
+            let entries = Rc::clone(&entries);
+            move |result| if let ListResult::Item(info) = result { entries.borrow_mut().push(SourceOutputEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entries.take());
+    }
+
+    fn cards(&mut self) -> Result<Vec<CardEntry>, CliError> {
+        let entries = Rc::new(RefCell::new(Vec::new()));
+        let operation = self.context.introspect().get_card_info_list({
+            let entries = Rc::clone(&entries);
+            move |result| if let ListResult::Item(info) = result { entries.borrow_mut().push(CardEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entries.take());
+    }
+
+    fn sink(&mut self, index: u32) -> Result<Option<SinkEntry>, CliError> {
+        let entry = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_sink_info_by_index(index, {
+            let entry = Rc::clone(&entry);
+            move |result| if let ListResult::Item(info) = result { *entry.borrow_mut() = Some(SinkEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entry.take());
+    }
+
+    fn source(&mut self, index: u32) -> Result<Option<SourceEntry>, CliError> {
+        let entry = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_source_info_by_index(index, {
+            let entry = Rc::clone(&entry);
+            move |result| if let ListResult::Item(info) = result { *entry.borrow_mut() = Some(SourceEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entry.take());
+    }
+
+    fn sink_input(&mut self, index: u32) -> Result<Option<SinkInputEntry>, CliError> {
+        let entry = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_sink_input_info(index, {
+            let entry = Rc::clone(&entry);
+            move |result| if let ListResult::Item(info) = result { *entry.borrow_mut() = Some(SinkInputEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entry.take());
+    }
+
+    fn source_output(&mut self, index: u32) -> Result<Option<SourceOutputEntry>, CliError> {
+        let entry = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_source_output_info(index, {
+            let entry = Rc::clone(&entry);
+            move |result| if let ListResult::Item(info) = result { *entry.borrow_mut() = Some(SourceOutputEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entry.take());
+    }
+
+    fn card(&mut self, index: u32) -> Result<Option<CardEntry>, CliError> {
+        let entry = Rc::new(RefCell::new(None));
+        let operation = self.context.introspect().get_card_info_by_index(index, {
+            let entry = Rc::clone(&entry);
+            move |result| if let ListResult::Item(info) = result { *entry.borrow_mut() = Some(CardEntry::from(info)); }
+        });
+        self.wait(operation)?;
+        return Ok(entry.take());
+    }
+}
+
+struct ServerInfo {
+    server_name:         Option<String>,
+    server_version:      Option<String>,
+    default_sink_name:   Option<String>,
+    default_source_name: Option<String>,
+}
+
+impl ToJson for ServerInfo {
+    fn to_json(&self) -> serde_json::Value {
+        return serde_json::json!({
+            "server_name":    self.server_name,
+            "server_version": self.server_version,
+            "default_sink":   self.default_sink_name,
+            "default_source": self.default_source_name,
+        });
+    }
+}
+
+/// Entries that can be referred to on the command line.
+trait Named {
+    fn index(&self) -> u32;
+    fn name(&self) -> &str;
+    /// The name as shown in the interface.
+    fn label(&self) -> String;
+}
+
+impl Named for SinkEntry {
+    fn index(&self) -> u32 { return self.index; }
+    fn name(&self) -> &str { return &self.name; }
+    fn label(&self) -> String { return self.display_name(); }
+}
+
+impl Named for SourceEntry {
+    fn index(&self) -> u32 { return self.index; }
+    fn name(&self) -> &str { return &self.name; }
+    fn label(&self) -> String { return self.display_name(); }
+}
+
+impl Named for SinkInputEntry {
+    fn index(&self) -> u32 { return self.index; }
+    fn name(&self) -> &str { return &self.name; }
+    fn label(&self) -> String { return self.display_name(); }
+}
+
+impl Named for SourceOutputEntry {
+    fn index(&self) -> u32 { return self.index; }
+    fn name(&self) -> &str { return &self.name; }
+    fn label(&self) -> String { return self.display_name(); }
+}
+
+impl Named for CardEntry {
+    fn index(&self) -> u32 { return self.index; }
+    fn name(&self) -> &str { return &self.name; }
+    fn label(&self) -> String { return self.display_name(); }
+}
+
+/// All entries matching `query`: the one with that index, else the ones with exactly that name,
+/// else the ones whose label contains it, ignoring case.
+fn find_all<'a, T: Named>(entries: &'a [T], query: &str) -> Vec<&'a T> {
+    if let Ok(index) = query.parse::<u32>() {
+        if let Some(entry) = entries.iter().find(|x| x.index() == index) {
+            return vec![entry];
+        }
+    }
+
+    let exact: Vec<&T> = entries.iter().filter(|x| x.name() == query).collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let query = query.to_lowercase();
+    return entries.iter().filter(|x| x.label().to_lowercase().contains(&query)).collect();
+}
+
+fn find_one<'a, T: Named>(entries: &'a [T], query: &str, kind: &str) -> Result<&'a T, CliError> {
+    let matches = find_all(entries, query);
+    match matches.as_slice() {
+        [entry] => return Ok(entry),
+        []      => return Err(CliError::NoMatch(format!("no {} matches '{}'", kind, query))),
+        _ => {
+            let labels: Vec<String> = matches.iter().map(|x| format!("  {}\t{}", x.index(), x.label())).collect();
+            return Err(CliError::NoMatch(format!("'{}' matches more than one {}:\n{}", query, kind, labels.join("\n"))));
+        }
+    }
+}
+
+fn find_sink<'a>(connection: &mut Connection, sinks: &'a [SinkEntry], query: &str) -> Result<&'a SinkEntry, CliError> {
+    let default_sink = if query == DEFAULT_SINK { connection.server_info()?.default_sink_name } else { None };
+    return select_sink(sinks, query, default_sink.as_deref());
+}
+
+/// Like `find_one`, with `@DEFAULT_SINK@` standing for the sink named `default_sink`.
+fn select_sink<'a>(sinks: &'a [SinkEntry], query: &str, default_sink: Option<&str>) -> Result<&'a SinkEntry, CliError> {
+    if query == DEFAULT_SINK {
+        let name = default_sink.ok_or_else(|| CliError::NoMatch(String::from("there is no default sink")))?;
+        return find_one(sinks, name, "sink");
+    }
+    return find_one(sinks, query, "sink");
+}
+
+/// Parse a volume argument: `40%` sets the volume, `+5%` and `-5%` change it.
+fn parse_volume_change(arg: &str) -> Option<VolumeChange> {
+    if let Some(rest) = arg.strip_prefix('+') {
+        return crate::parse_volume_percent(rest).map(VolumeChange::Increase);
+    }
+    if let Some(rest) = arg.strip_prefix('-') {
+        return crate::parse_volume_percent(rest).map(VolumeChange::Decrease);
+    }
+    return crate::parse_volume_percent(arg).map(VolumeChange::Set);
+}
+
+fn volume_label(volume: &ChannelVolumes, mute: bool) -> String {
+    let volume_ratio = volume.avg().0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
+    let mut label = format!("{:.0}%", volume_ratio * 100f64);
+    if mute {
+        label += " (muted)";
+    }
+    return label;
+}
+
+fn own_streams_removed(streams: Vec<SourceOutputEntry>) -> Vec<SourceOutputEntry> {
+    return streams.into_iter().filter(|x| !crate::is_own_stream(&x.proplist)).collect();
+}
+
+fn dump(connection: &mut Connection) -> Result<(), CliError> {
+    let state = serde_json::json!({
+        "server":         connection.server_info()?.to_json(),
+        "sinks":          crate::json::list(&connection.sinks()?),
+        "sources":        crate::json::list(&connection.sources()?),
+        "sink_inputs":    crate::json::list(&connection.sink_inputs()?),
+        "source_outputs": crate::json::list(&own_streams_removed(connection.source_outputs()?)),
+        "cards":          crate::json::list(&connection.cards()?),
+    });
+    println!("{}", state);
+    return Ok(());
+}
+
+fn list(connection: &mut Connection, kind: &str, json: bool) -> Result<(), CliError> {
+    if json {
+        let entries = match kind {
+            "sinks"          => crate::json::list(&connection.sinks()?),
+            "sources"        => crate::json::list(&connection.sources()?),
+            "sink-inputs"    => crate::json::list(&connection.sink_inputs()?),
+            "source-outputs" => crate::json::list(&own_streams_removed(connection.source_outputs()?)),
+            "cards"          => crate::json::list(&connection.cards()?),
+            _ => return Err(CliError::Usage(format!("unknown list '{}'", kind))),
+        };
+        println!("{}", entries);
+        return Ok(());
+    }
+
+    match kind {
+        "sinks" => {
+            let default_sink = connection.server_info()?.default_sink_name;
+            for sink in connection.sinks()? {
+                let default = if default_sink.as_deref() == Some(sink.name.as_str()) { "*" } else { " " };
+                println!("{}{}\t{}\t{}\t{}", default, sink.index, sink.name, volume_label(&sink.volume, sink.mute), sink.display_name());
+            }
+        }
+        "sources" => {
+            for source in connection.sources()? {
+                println!("{}\t{}\t{}\t{}", source.index, source.name, volume_label(&source.volume, source.mute), source.display_name());
+            }
+        }
+        "sink-inputs" => {
+            for stream in connection.sink_inputs()? {
+                println!("{}\t{}\t{}\t{}", stream.index, stream.sink_index, volume_label(&stream.volume, stream.mute), stream.display_name());
+            }
+        }
+        "source-outputs" => {
+            for stream in own_streams_removed(connection.source_outputs()?) {
+                println!("{}\t{}\t{}\t{}", stream.index, stream.source_index, volume_label(&stream.volume, stream.mute), stream.display_name());
+            }
+        }
+        "cards" => {
+            for card in connection.cards()? {
+                println!("{}\t{}\t{}", card.index, card.name, card.display_name());
+                for (i, profile) in card.profiles.iter().enumerate() {
+                    let active = if card.active_profile_index == Some(i) { "*" } else { " " };
+                    println!("  {} {}\t{}", active, profile.name, profile.display_name());
+                }
+            }
+        }
+        _ => return Err(CliError::Usage(format!("unknown list '{}'", kind))),
+    }
+    return Ok(());
+}
+
+fn print_event(facility: &str, event: Event, index: u32, object: Option<serde_json::Value>) {
+    let event = match event {
+        Event::New     => "new",
+        Event::Changed => "changed",
+        Event::Removed => "removed",
+    };
+    println!("{}", serde_json::json!({
+        "facility": facility,
+        "event":    event,
+        "index":    index,
+        "object":   object,
+    }));
+}
+
+/// Print the current state as `new` events, followed by an event for every change, each with the
+/// updated object.
+fn watch(connection: &mut Connection) -> Result<(), CliError> {
+    let events = Rc::new(RefCell::new(VecDeque::new()));
+    connection.context.set_subscribe_callback(Some(Box::new({
+        let events = Rc::clone(&events);
+        move |facility, operation, index| {
+            if let (Some(facility), Some(operation)) = (facility, operation) {
+                events.borrow_mut().push_back((facility, operation, index));
+            }
+        }
+    })));
+
+    let interest = InterestMaskSet::SERVER |
+        InterestMaskSet::SINK_INPUT |
+        InterestMaskSet::SINK |
+        InterestMaskSet::SOURCE_OUTPUT |
+        InterestMaskSet::SOURCE |
+        InterestMaskSet::CARD;
+    let operation = connection.context.subscribe(interest, |_| {});
+    connection.wait(operation)?;
+
+    let server_info = connection.server_info()?;
+    print_event("server", Event::New, 0, Some(server_info.to_json()));
+    for entry in connection.sinks()? {
+        print_event("sink", Event::New, entry.index, Some(entry.to_json()));
+    }
+    for entry in connection.sources()? {
+        print_event("source", Event::New, entry.index, Some(entry.to_json()));
+    }
+    for entry in connection.sink_inputs()? {
+        print_event("sink-input", Event::New, entry.index, Some(entry.to_json()));
+    }
+    // the level meter streams of running instances are left out, like in the interface
+    let (own, source_outputs): (Vec<SourceOutputEntry>, Vec<SourceOutputEntry>) = connection.source_outputs()?
+        .into_iter()
+        .partition(|x| crate::is_own_stream(&x.proplist));
+    let mut own_streams: HashSet<u32> = own.iter().map(|x| x.index).collect();
+    for entry in source_outputs {
+        print_event("source-output", Event::New, entry.index, Some(entry.to_json()));
+    }
+    for entry in connection.cards()? {
+        print_event("card", Event::New, entry.index, Some(entry.to_json()));
+    }
+
+    loop {
+        connection.iterate()?;
+
+        loop {
+            // the borrow must end before waiting for operations, which may queue more events
+            let next = events.borrow_mut().pop_front();
+            let (facility, event, index) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            if event == Event::Removed {
+                let facility = match facility {
+                    Facility::Sink         => "sink",
+                    Facility::Source       => "source",
+                    Facility::SinkInput    => "sink-input",
+                    Facility::SourceOutput => {
+                        if own_streams.remove(&index) {
+                            continue;
+                        }
+                        "source-output"
+                    }
+                    Facility::Card         => "card",
+                    _ => continue,
+                };
+                print_event(facility, event, index, None);
+                continue;
+            }
+
+            // objects may already be gone again, their removal is reported by the next event
+            match facility {
+                Facility::Server => {
+                    print_event("server", event, index, Some(connection.server_info()?.to_json()));
+                }
+                Facility::Sink => {
+                    if let Some(entry) = connection.sink(index)? {
+                        print_event("sink", event, index, Some(entry.to_json()));
+                    }
+                }
+                Facility::Source => {
+                    if let Some(entry) = connection.source(index)? {
+                        print_event("source", event, index, Some(entry.to_json()));
+                    }
+                }
+                Facility::SinkInput => {
+                    if let Some(entry) = connection.sink_input(index)? {
+                        print_event("sink-input", event, index, Some(entry.to_json()));
+                    }
+                }
+                Facility::SourceOutput => {
+                    if let Some(entry) = connection.source_output(index)? {
+                        if crate::is_own_stream(&entry.proplist) {
+                            own_streams.insert(index);
+                            continue;
+                        }
+                        print_event("source-output", event, index, Some(entry.to_json()));
+                    }
+                }
+                Facility::Card => {
+                    if let Some(entry) = connection.card(index)? {
+                        print_event("card", event, index, Some(entry.to_json()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn set_volume(connection: &mut Connection, sink: &str, volume: &str, max_volume: u32) -> Result<(), CliError> {
+    let change = parse_volume_change(volume)
+        .ok_or_else(|| CliError::Usage(format!("invalid volume '{}'", volume)))?;
+    let sinks = connection.sinks()?;
+    let sink = find_sink(connection, &sinks, sink)?;
+
+    let new_vol = ChannelState::default().change_volume(&sink.volume, change, max_volume);
+    connection.execute(|introspect, callback| introspect.set_sink_volume_by_index(sink.index, &new_vol, Some(callback)))?;
+    println!("{}: {}", sink.display_name(), volume_label(&new_vol, sink.mute));
+    return Ok(());
+}
+
+fn mute_toggle(connection: &mut Connection, sink: &str) -> Result<(), CliError> {
+    let sinks = connection.sinks()?;
+    let sink = find_sink(connection, &sinks, sink)?;
+
+    connection.execute(|introspect, callback| introspect.set_sink_mute_by_index(sink.index, !sink.mute, Some(callback)))?;
+    println!("{}: {}", sink.display_name(), if sink.mute { "unmuted" } else { "muted" });
+    return Ok(());
+}
+
+fn move_input(connection: &mut Connection, stream: &str, sink: &str) -> Result<(), CliError> {
+    let sinks = connection.sinks()?;
+    let sink = find_sink(connection, &sinks, sink)?;
+    let streams = connection.sink_inputs()?;
+    let matches = find_all(&streams, stream);
+    if matches.is_empty() {
+        return Err(CliError::NoMatch(format!("no sink input matches '{}'", stream)));
+    }
+
+    for stream in matches {
+        connection.execute(|introspect, callback| introspect.move_sink_input_by_index(stream.index, sink.index, Some(callback)))?;
+        println!("{} -> {}", stream.display_name(), sink.display_name());
+    }
+    return Ok(());
+}
+
+fn set_profile(connection: &mut Connection, card: &str, profile: &str) -> Result<(), CliError> {
+    let cards = connection.cards()?;
+    let card = find_one(&cards, card, "card")?;
+    let profile = card.profiles.iter().find(|p| p.name == profile)
+        .or_else(|| card.profiles.iter().find(|p| p.description.to_lowercase() == profile.to_lowercase()))
+        .ok_or_else(|| CliError::NoMatch(format!("card '{}' has no profile '{}'", card.display_name(), profile)))?;
+
+    connection.execute(|introspect, callback| introspect.set_card_profile_by_index(card.index, &profile.name, Some(callback)))?;
+    println!("{}: {}", card.display_name(), profile.display_name());
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_sink;
+
+    fn sinks() -> Vec<SinkEntry> {
+        return vec![
+            test_sink(1, "alsa_output.pci.analog-stereo"),
+            test_sink(2, "alsa_output.usb.headset"),
+            test_sink(3, "alsa_output.usb.headset.monitor-mix"),
+            test_sink(40, "4"),
+        ];
+    }
+
+    fn found(result: Result<&SinkEntry, CliError>) -> u32 {
+        return match result {
+            Ok(sink) => sink.index,
+            Err(e)   => panic!("no single match, exit code {}", e.exit_code()),
+        };
+    }
+
+    fn exit_code(result: Result<&SinkEntry, CliError>) -> i32 {
+        return match result {
+            Ok(sink) => panic!("'{}' should not match", sink.name),
+            Err(e)   => e.exit_code(),
+        };
+    }
+
+    #[test]
+    fn find_by_index_name_and_substring() {
+        let sinks = sinks();
+        assert_eq!(found(find_one(&sinks, "2", "sink")), 2);
+        // an index wins over a name that is a number
+        assert_eq!(found(find_one(&sinks, "4", "sink")), 40);
+        // an exact name wins over the names containing it
+        assert_eq!(found(find_one(&sinks, "alsa_output.usb.headset", "sink")), 2);
+        assert_eq!(found(find_one(&sinks, "ANALOG", "sink")), 1);
+        assert_eq!(found(find_one(&sinks, "monitor", "sink")), 3);
+    }
+
+    #[test]
+    fn find_ambiguous_or_nothing() {
+        let sinks = sinks();
+        let matches: Vec<u32> = find_all(&sinks, "usb").iter().map(|x| x.index).collect();
+        assert_eq!(matches, vec![2, 3]);
+        assert_eq!(exit_code(find_one(&sinks, "usb", "sink")), EXIT_NO_MATCH);
+        assert_eq!(exit_code(find_one(&sinks, "hdmi", "sink")), EXIT_NO_MATCH);
+        assert!(find_all(&sinks, "hdmi").is_empty());
+    }
+
+    #[test]
+    fn find_default_sink() {
+        let sinks = sinks();
+        assert_eq!(found(select_sink(&sinks, DEFAULT_SINK, Some("alsa_output.usb.headset"))), 2);
+        assert_eq!(exit_code(select_sink(&sinks, DEFAULT_SINK, None)), EXIT_NO_MATCH);
+        assert_eq!(found(select_sink(&sinks, "analog", None)), 1);
+    }
+
+    #[test]
+    fn volume_changes() {
+        let percent = crate::volume_from_percent;
+        assert_eq!(parse_volume_change("+5%"), Some(VolumeChange::Increase(percent(5))));
+        assert_eq!(parse_volume_change("-5%"), Some(VolumeChange::Decrease(percent(5))));
+        assert_eq!(parse_volume_change("50%"), Some(VolumeChange::Set(percent(50))));
+        assert_eq!(parse_volume_change("50"), Some(VolumeChange::Set(percent(50))));
+        assert_eq!(parse_volume_change("+10"), Some(VolumeChange::Increase(percent(10))));
+        assert_eq!(parse_volume_change("loud"), None);
+        assert_eq!(parse_volume_change("5%%"), None);
+        assert_eq!(parse_volume_change("+-5%"), None);
+        assert_eq!(parse_volume_change(""), None);
+    }
+}
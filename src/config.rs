@@ -0,0 +1,851 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+use termion::event::Key;
+
+use crate::AppView;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    // global
+    Quit,
+    NextView,
+    ShowView(AppView),
+    ToggleMeters,
+    ToggleMonitors,
+    SwitchServer,
+    Properties,
+    Search,
+    NextMatch,
+    PrevMatch,
+    CycleSort,
+
+    // common to all views and their popups
+    Help,
+    ClosePopup,
+    Down,
+    Up,
+    Confirm,
+
+    // volume views
+    ToggleMute,
+    VolumeDown,
+    VolumeUp,
+    VolumeDownBig,
+    VolumeUpBig,
+    VolumeMin,
+    VolumeNormal,
+    /// Set the volume to the given multiple of 10%.
+    VolumeLevel(u32),
+    ToggleChannels,
+    LockChannels,
+    NextChannel,
+    PrevChannel,
+    BalanceLeft,
+    BalanceRight,
+    FadeRear,
+    FadeFront,
+    LfeDown,
+    LfeUp,
+    ResetBalance,
+
+    // sinks and sources
+    ChoosePort,
+    SetDefault,
+    SetDefaultMoveAll,
+    UnloadModule,
+
+    // streams
+    ChooseDevice,
+    Kill,
+    KillInactive,
+    ToggleGroups,
+    ExpandGroup,
+
+    // cards
+    NextProfile,
+    PrevProfile,
+
+    // modules
+    LoadModule,
+}
+
+impl Action {
+    /// Name used in the config file.
+    pub fn name(&self) -> String {
+        let name = match self {
+            Action::Quit              => "quit",
+            Action::NextView          => "next-view",
+            Action::ShowView(view)    => return format!("show-{}", view_name(*view)),
+            Action::ToggleMeters      => "toggle-meters",
+            Action::ToggleMonitors    => "toggle-monitors",
+            Action::SwitchServer      => "switch-server",
+            Action::Properties        => "properties",
+            Action::Search            => "search",
+            Action::NextMatch         => "next-match",
+            Action::PrevMatch         => "prev-match",
+            Action::CycleSort         => "cycle-sort",
+            Action::Help              => "help",
+            Action::ClosePopup        => "close-popup",
+            Action::Down              => "down",
+            Action::Up                => "up",
+            Action::Confirm           => "confirm",
+            Action::ToggleMute        => "toggle-mute",
+            Action::VolumeDown        => "volume-down",
+            Action::VolumeUp          => "volume-up",
+            Action::VolumeDownBig     => "volume-down-big",
+            Action::VolumeUpBig       => "volume-up-big",
+            Action::VolumeMin         => "volume-min",
+            Action::VolumeNormal      => "volume-normal",
+            Action::VolumeLevel(n)    => return format!("volume-{}", n * 10),
+            Action::ToggleChannels    => "toggle-channels",
+            Action::LockChannels      => "lock-channels",
+            Action::NextChannel       => "next-channel",
+            Action::PrevChannel       => "prev-channel",
+            Action::BalanceLeft       => "balance-left",
+            Action::BalanceRight      => "balance-right",
+            Action::FadeRear          => "fade-rear",
+            Action::FadeFront         => "fade-front",
+            Action::LfeDown           => "lfe-down",
+            Action::LfeUp             => "lfe-up",
+            Action::ResetBalance      => "reset-balance",
+            Action::ChoosePort        => "choose-port",
+            Action::SetDefault        => "set-default",
+            Action::SetDefaultMoveAll => "set-default-move-all",
+            Action::UnloadModule      => "unload-module",
+            Action::ChooseDevice      => "choose-device",
+            Action::Kill              => "kill",
+            Action::KillInactive      => "kill-inactive",
+            Action::ToggleGroups      => "toggle-groups",
+            Action::ExpandGroup       => "expand-group",
+            Action::NextProfile       => "next-profile",
+            Action::PrevProfile       => "prev-profile",
+            Action::LoadModule        => "load-module",
+        };
+        return String::from(name);
+    }
+
+    /// Description shown in the keybinding popup.
+    pub fn description(&self, section: KeymapSection) -> String {
+        let description = match (self, section) {
+            (Action::Quit, _)                            => "Quit",
+            (Action::NextView, _)                        => "Cycle tabs",
+            (Action::ShowView(view), _)                  => return format!("Show {}", view_title(*view)),
+            (Action::ToggleMeters, _)                    => "Toggle level meters",
+            (Action::ToggleMonitors, _)                  => "Show / hide monitor sources",
+            (Action::SwitchServer, _)                    => "Switch sound server",
+            (Action::Properties, _)                      => "Show properties of the selected entry",
+            (Action::Search, _)                          => "Search in the current view",
+            (Action::NextMatch, _)                       => "Next search match",
+            (Action::PrevMatch, _)                       => "Previous search match",
+            (Action::CycleSort, _)                       => "Change sort order",
+            (Action::Help, _)                            => "Hotkeys",
+            (Action::ClosePopup, _)                      => "Close popup",
+            (Action::Down, _)                            => "Move down",
+            (Action::Up, _)                              => "Move up",
+            (Action::Confirm, KeymapSection::Cards)      => "Confirm profile",
+            (Action::Confirm, _)                         => "Confirm popup selection",
+            (Action::ToggleMute, _)                      => "Toggle mute",
+            (Action::VolumeDown, _)                      => "Volume down",
+            (Action::VolumeUp, _)                        => "Volume up",
+            (Action::VolumeDownBig, _)                   => "Volume down (big steps)",
+            (Action::VolumeUpBig, _)                     => "Volume up (big steps)",
+            (Action::VolumeMin, _)                       => "Volume 0%",
+            (Action::VolumeNormal, _)                    => "Volume 100%",
+            (Action::VolumeLevel(n), _)                  => return format!("Volume {}%", n * 10),
+            (Action::ToggleChannels, _)                  => "Show / hide channels",
+            (Action::LockChannels, _)                    => "Lock / unlock channels",
+            (Action::NextChannel, _)                     => "Select next channel",
+            (Action::PrevChannel, _)                     => "Select previous channel",
+            (Action::BalanceLeft, _)                     => "Balance left",
+            (Action::BalanceRight, _)                    => "Balance right",
+            (Action::FadeRear, _)                        => "Fade rear",
+            (Action::FadeFront, _)                       => "Fade front",
+            (Action::LfeDown, _)                         => "LFE balance down",
+            (Action::LfeUp, _)                           => "LFE balance up",
+            (Action::ResetBalance, _)                    => "Reset balance, fade and LFE",
+            (Action::ChoosePort, _)                      => "Choose port",
+            (Action::SetDefault, _)                      => "Set as default",
+            (Action::SetDefaultMoveAll, _)               => "Set as default and move all streams",
+            (Action::UnloadModule, KeymapSection::Sinks) => "Unload owner module (remove sink)",
+            (Action::UnloadModule, KeymapSection::Modules) => "Unload module",
+            (Action::UnloadModule, _)                    => "Unload owner module (remove source)",
+            (Action::ChooseDevice, KeymapSection::SinkInputs) => "Choose sink for selected stream",
+            (Action::ChooseDevice, _)                    => "Choose source for selected stream",
+            (Action::Kill, _)                            => "Kill stream",
+            (Action::KillInactive, _)                    => "Kill all non-running streams",
+            (Action::ToggleGroups, _)                    => "Group streams by application",
+            (Action::ExpandGroup, _)                     => "Expand / collapse application",
+            (Action::NextProfile, _)                     => "Select next profile for current card",
+            (Action::PrevProfile, _)                     => "Select previous profile for current card",
+            (Action::LoadModule, _)                      => "Load a module",
+        };
+        return String::from(description);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeymapSection {
+    Global,
+    SinkInputs,
+    SourceOutputs,
+    Sinks,
+    Sources,
+    Cards,
+    Modules,
+}
+
+impl KeymapSection {
+    const ALL: [KeymapSection; 7] = [
+        KeymapSection::Global,
+        KeymapSection::SinkInputs,
+        KeymapSection::SourceOutputs,
+        KeymapSection::Sinks,
+        KeymapSection::Sources,
+        KeymapSection::Cards,
+        KeymapSection::Modules,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            KeymapSection::Global        => "global",
+            KeymapSection::SinkInputs    => view_name(AppView::SinkInputs),
+            KeymapSection::SourceOutputs => view_name(AppView::SourceOutputs),
+            KeymapSection::Sinks         => view_name(AppView::Sinks),
+            KeymapSection::Sources       => view_name(AppView::Sources),
+            KeymapSection::Cards         => view_name(AppView::Cards),
+            KeymapSection::Modules       => view_name(AppView::Modules),
+        }
+    }
+}
+
+impl From<AppView> for KeymapSection {
+    fn from(view: AppView) -> KeymapSection {
+        match view {
+            AppView::SinkInputs    => KeymapSection::SinkInputs,
+            AppView::SourceOutputs => KeymapSection::SourceOutputs,
+            AppView::Sinks         => KeymapSection::Sinks,
+            AppView::Sources       => KeymapSection::Sources,
+            AppView::Cards         => KeymapSection::Cards,
+            AppView::Modules       => KeymapSection::Modules,
+        }
+    }
+}
+
+pub fn view_name(view: AppView) -> &'static str {
+    match view {
+        AppView::SinkInputs    => "sink-inputs",
+        AppView::SourceOutputs => "source-outputs",
+        AppView::Sinks         => "sinks",
+        AppView::Sources       => "sources",
+        AppView::Cards         => "cards",
+        AppView::Modules       => "modules",
+    }
+}
+
+fn view_title(view: AppView) -> &'static str {
+    match view {
+        AppView::SinkInputs    => "sink inputs",
+        AppView::SourceOutputs => "source outputs",
+        AppView::Sinks         => "sinks",
+        AppView::Sources       => "sources",
+        AppView::Cards         => "cards",
+        AppView::Modules       => "modules",
+    }
+}
+
+pub fn parse_view(name: &str) -> Option<AppView> {
+    return crate::draw::TAB_VIEWS.iter().copied().find(|view| view_name(*view) == name);
+}
+
+/// Parse a key as written in the config file, e.g. `j`, `ctrl-h`, `F1` or `return`.
+pub fn parse_key(s: &str) -> Option<Key> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+
+    let single_char = |rest: &str| -> Option<char> {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(rest) = s.strip_prefix("ctrl-") {
+        return single_char(rest).map(|c| Key::Ctrl(c.to_ascii_lowercase()));
+    }
+    if let Some(rest) = s.strip_prefix("alt-") {
+        return single_char(rest).map(Key::Alt);
+    }
+    if let Some(n) = s.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+        return Some(Key::F(n));
+    }
+
+    let key = match s {
+        "return" | "enter" => Key::Char('\n'),
+        "tab"              => Key::Char('\t'),
+        "space"            => Key::Char(' '),
+        "backtab"          => Key::BackTab,
+        "esc"              => Key::Esc,
+        "backspace"        => Key::Backspace,
+        "delete"           => Key::Delete,
+        "insert"           => Key::Insert,
+        "left"             => Key::Left,
+        "right"            => Key::Right,
+        "up"               => Key::Up,
+        "down"             => Key::Down,
+        "home"             => Key::Home,
+        "end"              => Key::End,
+        "pageup"           => Key::PageUp,
+        "pagedown"         => Key::PageDown,
+        _                  => return None,
+    };
+    return Some(key);
+}
+
+/// Inverse of `parse_key`, used for the keybinding popups.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\n') => String::from("return"),
+        Key::Char('\t') => String::from("tab"),
+        Key::Char(' ')  => String::from("space"),
+        Key::Char(c)    => c.to_string(),
+        Key::Ctrl(c)    => format!("ctrl-{}", c),
+        Key::Alt(c)     => format!("alt-{}", c),
+        Key::F(n)       => format!("F{}", n),
+        Key::BackTab    => String::from("backtab"),
+        Key::Esc        => String::from("esc"),
+        Key::Backspace  => String::from("backspace"),
+        Key::Delete     => String::from("delete"),
+        Key::Insert     => String::from("insert"),
+        Key::Left       => String::from("left"),
+        Key::Right      => String::from("right"),
+        Key::Up         => String::from("up"),
+        Key::Down       => String::from("down"),
+        Key::Home       => String::from("home"),
+        Key::End        => String::from("end"),
+        Key::PageUp     => String::from("pageup"),
+        Key::PageDown   => String::from("pagedown"),
+        _               => String::from("?"),
+    }
+}
+
+/// Key bindings per section. Bindings are kept in order, so the keybinding popups list them in a
+/// sensible order.
+pub struct Keymap {
+    sections: HashMap<KeymapSection, Vec<(Action, Vec<Key>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let mut global = vec![
+            (Action::Quit,           vec![Key::Char('q'), Key::Ctrl('c')]),
+            (Action::NextView,       vec![Key::Char('\t')]),
+        ];
+        for (i, view) in crate::draw::TAB_VIEWS.iter().enumerate() {
+            global.push((Action::ShowView(*view), vec![Key::F(i as u8 + 1)]));
+        }
+        global.push((Action::ToggleMeters,   vec![Key::Char('P')]));
+        global.push((Action::ToggleMonitors, vec![Key::Char('M')]));
+        global.push((Action::SwitchServer,   vec![Key::Char('S')]));
+        global.push((Action::Properties,     vec![Key::Char('I')]));
+        global.push((Action::Search,         vec![Key::Char('/')]));
+        global.push((Action::NextMatch,      vec![Key::Char('n')]));
+        global.push((Action::PrevMatch,      vec![Key::Char('N')]));
+        global.push((Action::CycleSort,      vec![Key::Char('o')]));
+
+        let common = [
+            (Action::Help,       vec![Key::Char('?')]),
+            (Action::ClosePopup, vec![Key::Esc]),
+            (Action::Down,       vec![Key::Char('j'), Key::Down]),
+            (Action::Up,         vec![Key::Char('k'), Key::Up]),
+        ];
+
+        let mut volume = vec![
+            (Action::ToggleMute,     vec![Key::Char('m')]),
+            (Action::VolumeDown,     vec![Key::Char('h'), Key::Left]),
+            (Action::VolumeUp,       vec![Key::Char('l'), Key::Right]),
+            (Action::VolumeDownBig,  vec![Key::Char('H')]),
+            (Action::VolumeUpBig,    vec![Key::Char('L')]),
+            (Action::VolumeMin,      vec![Key::Ctrl('h')]),
+            (Action::VolumeNormal,   vec![Key::Ctrl('l')]),
+        ];
+        for (n, c) in "^1234567890".chars().enumerate() {
+            volume.push((Action::VolumeLevel(n as u32), vec![Key::Char(c)]));
+        }
+        volume.extend(vec![
+            (Action::ToggleChannels, vec![Key::Char('c')]),
+            (Action::LockChannels,   vec![Key::Char('C')]),
+            (Action::PrevChannel,    vec![Key::Char('[')]),
+            (Action::NextChannel,    vec![Key::Char(']')]),
+        ]);
+
+        let balance = vec![
+            (Action::BalanceLeft,  vec![Key::Char(',')]),
+            (Action::BalanceRight, vec![Key::Char('.')]),
+            (Action::FadeRear,     vec![Key::Char('<')]),
+            (Action::FadeFront,    vec![Key::Char('>')]),
+            (Action::LfeDown,      vec![Key::Char('(')]),
+            (Action::LfeUp,        vec![Key::Char(')')]),
+            (Action::ResetBalance, vec![Key::Char('=')]),
+        ];
+
+        let devices = vec![
+            (Action::ChoosePort,        vec![Key::Char('p')]),
+            (Action::Confirm,           vec![Key::Char('\n')]),
+            (Action::SetDefault,        vec![Key::Char('d')]),
+            (Action::SetDefaultMoveAll, vec![Key::Ctrl('d')]),
+            (Action::UnloadModule,      vec![Key::Char('D')]),
+        ];
+
+        let streams = vec![
+            (Action::ChooseDevice, vec![Key::Char('i'), Key::Char('\n')]),
+            (Action::Confirm,      vec![Key::Char('\n')]),
+            (Action::Kill,         vec![Key::Char('K')]),
+            (Action::KillInactive, vec![Key::Ctrl('k')]),
+            (Action::ToggleGroups, vec![Key::Char('g')]),
+            (Action::ExpandGroup,  vec![Key::Char('e')]),
+        ];
+
+        let cards = vec![
+            (Action::NextProfile, vec![Key::Char('+')]),
+            (Action::PrevProfile, vec![Key::Char('-')]),
+            (Action::Confirm,     vec![Key::Char('\n')]),
+        ];
+
+        let modules = vec![
+            (Action::LoadModule,   vec![Key::Char('a')]),
+            (Action::UnloadModule, vec![Key::Char('D')]),
+            (Action::Confirm,      vec![Key::Char('\n')]),
+        ];
+
+        let mut sections = HashMap::new();
+        sections.insert(KeymapSection::Global,        global);
+        sections.insert(KeymapSection::SinkInputs,    [&common[..], &volume, &balance, &streams].concat());
+        sections.insert(KeymapSection::SourceOutputs, [&common[..], &volume, &streams].concat());
+        sections.insert(KeymapSection::Sinks,         [&common[..], &volume, &balance, &devices].concat());
+        sections.insert(KeymapSection::Sources,       [&common[..], &volume, &devices].concat());
+        sections.insert(KeymapSection::Cards,         [&common[..], &cards].concat());
+        sections.insert(KeymapSection::Modules,       [&common[..], &modules].concat());
+
+        return Keymap { sections };
+    }
+}
+
+impl Keymap {
+    /// Look up the action bound to a key.
+    pub fn action(&self, section: KeymapSection, key: Key) -> Option<Action> {
+        return self.find(section, key, |_| true);
+    }
+
+    /// Look up the action bound to a key while a popup is open. Only the popup actions are
+    /// considered, as a key like return may be bound to a different action outside of popups.
+    pub fn popup_action(&self, section: KeymapSection, key: Key) -> Option<Action> {
+        return self.find(section, key, is_popup_action);
+    }
+
+    fn find(&self, section: KeymapSection, key: Key, filter: impl Fn(Action) -> bool) -> Option<Action> {
+        return self.sections.get(&section)?
+            .iter()
+            .find(|(action, keys)| filter(*action) && keys.contains(&key))
+            .map(|(action, _)| *action);
+    }
+
+    /// Lines for the keybinding popup of a view: key names and description of the view specific
+    /// and the global actions.
+    pub fn help(&self, section: KeymapSection) -> Vec<(String, String)> {
+        let mut lines = Vec::new();
+        for s in [KeymapSection::Global, section].iter() {
+            for (action, keys) in self.sections.get(s).into_iter().flatten() {
+                if keys.is_empty() {
+                    continue;
+                }
+                let key_names: Vec<String> = keys.iter().map(|k| key_name(*k)).collect();
+                lines.push((key_names.join("  "), action.description(section)));
+            }
+        }
+        return lines;
+    }
+
+    /// Make sure no key triggers two actions. Within a section, confirm may share its key with an
+    /// action that is not used in popups, as only one of them is looked up at a time. Keys of the
+    /// views must not shadow a global key.
+    fn check_conflicts(&self) -> Result<(), String> {
+        let global = &self.sections[&KeymapSection::Global];
+        for section in KeymapSection::ALL.iter() {
+            let bindings = &self.sections[section];
+            for (i, (action, keys)) in bindings.iter().enumerate() {
+                for key in keys.iter() {
+                    let other = bindings[i + 1..].iter()
+                        .find(|(other, other_keys)| other_keys.contains(key) && actions_conflict(*action, *other));
+                    if let Some((other, _)) = other {
+                        return Err(format!("key '{}' is bound to both '{}' and '{}' in [keys.{}]",
+                            key_name(*key), action.name(), other.name(), section.name()));
+                    }
+                    if *section == KeymapSection::Global {
+                        continue;
+                    }
+                    if let Some((other, _)) = global.iter().find(|(_, global_keys)| global_keys.contains(key)) {
+                        return Err(format!("key '{}' of '{}' in [keys.{}] is already bound to '{}' in [keys.global]",
+                            key_name(*key), action.name(), section.name(), other.name()));
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    fn rebind(&mut self, section: KeymapSection, action_name: &str, keys: Vec<Key>) -> Result<(), String> {
+        let bindings = self.sections.get_mut(&section).expect("Keymap section missing");
+        match bindings.iter_mut().find(|(action, _)| action.name() == action_name) {
+            Some((_, bound_keys)) => {
+                *bound_keys = keys;
+                return Ok(());
+            }
+            None => {
+                return Err(format!("unknown action '{}' in section [keys.{}]", action_name, section.name()));
+            }
+        }
+    }
+}
+
+/// Actions looked up while a popup is open.
+fn is_popup_action(action: Action) -> bool {
+    return matches!(action, Action::ClosePopup | Action::Confirm | Action::Down | Action::Up);
+}
+
+/// Whether two actions of the same section can not share a key.
+fn actions_conflict(a: Action, b: Action) -> bool {
+    let confirm_only = |x: Action, y: Action| x == Action::Confirm && !is_popup_action(y);
+    return !(confirm_only(a, b) || confirm_only(b, a));
+}
+
+#[derive(Clone, Copy)]
+pub struct Colors {
+    pub selected:          Color,
+    pub selected_inactive: Color,
+    pub inactive:          Color,
+    pub idle:              Color,
+    pub normal:            Color,
+    pub highlight:         Color,
+    pub popup_cursor:      Color,
+    pub popup_current:     Color,
+    pub overamplified:     Color,
+    pub meter:             Color,
+    pub balance:           Color,
+    pub status:            Color,
+    pub error:             Color,
+}
+
+impl Default for Colors {
+    fn default() -> Colors {
+        Colors {
+            selected:          Color::Green,
+            selected_inactive: Color::Gray,
+            inactive:          Color::DarkGray,
+            idle:              Color::Red,
+            normal:            Color::Yellow,
+            highlight:         Color::Yellow,
+            popup_cursor:      Color::Red,
+            popup_current:     Color::Green,
+            overamplified:     Color::Magenta,
+            meter:             Color::Cyan,
+            balance:           Color::Gray,
+            status:            Color::Gray,
+            error:             Color::Red,
+        }
+    }
+}
+
+impl Colors {
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let color = Color::from_str(value).map_err(|_| format!("invalid color '{}' for '{}'", value, name))?;
+        let field = match name {
+            "selected"          => &mut self.selected,
+            "selected-inactive" => &mut self.selected_inactive,
+            "inactive"          => &mut self.inactive,
+            "idle"              => &mut self.idle,
+            "normal"            => &mut self.normal,
+            "highlight"         => &mut self.highlight,
+            "popup-cursor"      => &mut self.popup_cursor,
+            "popup-current"     => &mut self.popup_current,
+            "overamplified"     => &mut self.overamplified,
+            "meter"             => &mut self.meter,
+            "balance"           => &mut self.balance,
+            "status"            => &mut self.status,
+            "error"             => &mut self.error,
+            _ => { return Err(format!("unknown color '{}'", name)); }
+        };
+        *field = color;
+        return Ok(());
+    }
+}
+
+pub struct Config {
+    pub keymap:            Keymap,
+    pub colors:            Colors,
+    pub volume_step_small: u32,
+    pub volume_step_big:   u32,
+    pub start_view:        AppView,
+    pub hide_monitors:     bool,
+    pub group_streams:     bool,
+    pub max_volume:        u32,
+    /// Server to connect to, `None` meaning the default one.
+    pub server:            Option<String>,
+    /// Servers offered for switching at runtime.
+    pub servers:           Vec<String>,
+    /// Rules for the names of streams, see `naming::set_rules`.
+    pub naming:            Vec<crate::naming::Rule>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            keymap:            Default::default(),
+            colors:            Default::default(),
+            volume_step_small: crate::VOLUME_STEP_SMALL,
+            volume_step_big:   crate::VOLUME_STEP_BIG,
+            start_view:        AppView::SinkInputs,
+            hide_monitors:     true,
+            group_streams:     false,
+            max_volume:        crate::volume_from_percent(crate::DEFAULT_MAX_VOLUME_PERCENT),
+            server:            None,
+            servers:           Vec::new(),
+            naming:            Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// A `[[names]]` entry of the config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NameRule {
+    #[serde(default, rename = "match")]
+    matches: HashMap<String, String>,
+    name:    String,
+}
+
+/// Layout of the config file. Everything is optional, missing entries keep their defaults.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    start_view:        Option<String>,
+    hide_monitors:     Option<bool>,
+    group_streams:     Option<bool>,
+    max_volume:        Option<u32>,
+    volume_step_small: Option<f64>,
+    volume_step_big:   Option<f64>,
+    server:            Option<String>,
+    servers:           Vec<String>,
+    names:             Vec<NameRule>,
+    colors:            HashMap<String, String>,
+    keys:              HashMap<String, HashMap<String, KeyList>>,
+}
+
+/// `$XDG_CONFIG_HOME/pavucontrolrs/config.toml`, falling back to `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    return Some(config_home.join("pavucontrolrs").join("config.toml"));
+}
+
+/// Load the config file, if there is one.
+pub fn load() -> Result<Config, String> {
+    let path = match config_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    return parse(&contents).map_err(|e| format!("{}: {}", path.display(), e));
+}
+
+pub fn parse(contents: &str) -> Result<Config, String> {
+    let file: ConfigFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+    let mut config = Config::default();
+
+    if let Some(name) = file.start_view {
+        config.start_view = parse_view(&name).ok_or_else(|| format!("unknown view '{}'", name))?;
+    }
+    if let Some(hide_monitors) = file.hide_monitors {
+        config.hide_monitors = hide_monitors;
+    }
+    if let Some(group_streams) = file.group_streams {
+        config.group_streams = group_streams;
+    }
+    if let Some(percent) = file.max_volume {
+        let range = crate::MAX_VOLUME_PERCENT_RANGE;
+        config.max_volume = crate::max_volume_from_percent(percent)
+            .ok_or_else(|| format!("invalid max-volume {}%, expected {}% to {}%", percent, range.start(), range.end()))?;
+    }
+    if let Some(percent) = file.volume_step_small {
+        config.volume_step_small = volume_step_from_percent(percent)?;
+    }
+    if let Some(percent) = file.volume_step_big {
+        config.volume_step_big = volume_step_from_percent(percent)?;
+    }
+    config.server = file.server;
+    config.servers = file.servers;
+
+    for rule in file.names.iter() {
+        config.naming.push(crate::naming::Rule::new(&rule.matches, &rule.name)?);
+    }
+
+    for (name, value) in file.colors.iter() {
+        config.colors.set(name, value)?;
+    }
+
+    for (section_name, bindings) in file.keys.iter() {
+        let section = KeymapSection::ALL.iter().copied().find(|s| s.name() == section_name)
+            .ok_or_else(|| format!("unknown keymap section [keys.{}]", section_name))?;
+        for (action_name, key_list) in bindings.iter() {
+            let key_names = match key_list {
+                KeyList::One(key)   => vec![key.clone()],
+                KeyList::Many(keys) => keys.clone(),
+            };
+            let keys = key_names.iter()
+                .map(|k| parse_key(k).ok_or_else(|| format!("invalid key '{}' for '{}'", k, action_name)))
+                .collect::<Result<Vec<Key>, String>>()?;
+            config.keymap.rebind(section, action_name, keys)?;
+        }
+    }
+    config.keymap.check_conflicts()?;
+
+    return Ok(config);
+}
+
+fn volume_step_from_percent(percent: f64) -> Result<u32, String> {
+    if !(percent > 0.0 && percent <= 100.0) {
+        return Err(format!("invalid volume step {}%", percent));
+    }
+    return Ok((pulse::volume::Volume::NORMAL.0 as f64 * percent / 100.0).round() as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(contents: &str) -> String {
+        return match parse(contents) {
+            Ok(_)  => panic!("config should not parse:\n{}", contents),
+            Err(e) => e,
+        };
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        let names = [
+            "j", "J", "^", "/", "ctrl-h", "alt-x", "F1", "F12", "return", "tab", "space", "backtab",
+            "esc", "backspace", "delete", "insert", "left", "right", "up", "down", "home", "end",
+            "pageup", "pagedown",
+        ];
+        for name in names.iter() {
+            let key = parse_key(name).unwrap_or_else(|| panic!("'{}' should parse", name));
+            assert_eq!(key_name(key), *name);
+        }
+        assert_eq!(parse_key("enter"), Some(Key::Char('\n')));
+        assert_eq!(parse_key("ctrl-H"), Some(Key::Ctrl('h')));
+        assert_eq!(parse_key("ctrl-hh"), None);
+        assert_eq!(parse_key("hyper-x"), None);
+    }
+
+    #[test]
+    fn default_keymap_matches_builtin_keys() {
+        let keymap = Keymap::default();
+        let global = [
+            (Key::Char('q'),  Action::Quit),
+            (Key::Ctrl('c'),  Action::Quit),
+            (Key::Char('\t'), Action::NextView),
+            (Key::F(1),       Action::ShowView(AppView::SinkInputs)),
+            (Key::F(6),       Action::ShowView(AppView::Modules)),
+            (Key::Char('/'),  Action::Search),
+        ];
+        for (key, action) in global.iter() {
+            assert_eq!(keymap.action(KeymapSection::Global, *key), Some(*action));
+        }
+        let sink_inputs = [
+            (Key::Char('j'),  Action::Down),
+            (Key::Up,         Action::Up),
+            (Key::Char('h'),  Action::VolumeDown),
+            (Key::Right,      Action::VolumeUp),
+            (Key::Ctrl('h'),  Action::VolumeMin),
+            (Key::Char('^'),  Action::VolumeLevel(0)),
+            (Key::Char('1'),  Action::VolumeLevel(1)),
+            (Key::Char('0'),  Action::VolumeLevel(10)),
+            (Key::Char('='),  Action::ResetBalance),
+            (Key::Char('\n'), Action::ChooseDevice),
+            (Key::Char('K'),  Action::Kill),
+        ];
+        for (key, action) in sink_inputs.iter() {
+            assert_eq!(keymap.action(KeymapSection::SinkInputs, *key), Some(*action));
+        }
+        assert_eq!(keymap.popup_action(KeymapSection::SinkInputs, Key::Char('\n')), Some(Action::Confirm));
+        assert_eq!(keymap.action(KeymapSection::SourceOutputs, Key::Char('=')), None);
+        assert_eq!(keymap.action(KeymapSection::Sinks, Key::Char('d')), Some(Action::SetDefault));
+        assert_eq!(keymap.action(KeymapSection::Cards, Key::Char('+')), Some(Action::NextProfile));
+        assert_eq!(keymap.action(KeymapSection::Modules, Key::Char('a')), Some(Action::LoadModule));
+        assert_eq!(keymap.check_conflicts(), Ok(()));
+    }
+
+    #[test]
+    fn rebind_replaces_keys() {
+        let config = parse("[keys.sink-inputs]\ndown = [\"x\", \"down\"]\nup = \"ctrl-p\"\n").unwrap();
+        let keymap = &config.keymap;
+        assert_eq!(keymap.action(KeymapSection::SinkInputs, Key::Char('x')), Some(Action::Down));
+        assert_eq!(keymap.action(KeymapSection::SinkInputs, Key::Char('j')), None);
+        assert_eq!(keymap.action(KeymapSection::SinkInputs, Key::Ctrl('p')), Some(Action::Up));
+        // other sections keep their keys
+        assert_eq!(keymap.action(KeymapSection::Sinks, Key::Char('j')), Some(Action::Down));
+    }
+
+    #[test]
+    fn swapped_keys_are_no_conflict() {
+        let config = parse("[keys.sinks]\ndown = \"k\"\nup = \"j\"\n").unwrap();
+        assert_eq!(config.keymap.action(KeymapSection::Sinks, Key::Char('k')), Some(Action::Down));
+        assert_eq!(config.keymap.action(KeymapSection::Sinks, Key::Char('j')), Some(Action::Up));
+    }
+
+    #[test]
+    fn conflicting_keys_are_rejected() {
+        assert_eq!(parse_err("[keys.sink-inputs]\nkill = \"j\"\n"),
+            "key 'j' is bound to both 'down' and 'kill' in [keys.sink-inputs]");
+        assert_eq!(parse_err("[keys.cards]\nconfirm = \"esc\"\n"),
+            "key 'esc' is bound to both 'close-popup' and 'confirm' in [keys.cards]");
+        assert_eq!(parse_err("[keys.modules]\nload-module = \"q\"\n"),
+            "key 'q' of 'load-module' in [keys.modules] is already bound to 'quit' in [keys.global]");
+    }
+
+    #[test]
+    fn unknown_entries_are_rejected() {
+        assert!(parse_err("volume-stpe-small = 5\n").contains("unknown field `volume-stpe-small`"));
+        assert!(parse_err("[[names]]\nname = \"x\"\nmatches = {}\n").contains("unknown field `matches`"));
+        assert_eq!(parse_err("[keys.sinks]\nfly = \"f\"\n"), "unknown action 'fly' in section [keys.sinks]");
+        assert_eq!(parse_err("[keys.speakers]\ndown = \"j\"\n"), "unknown keymap section [keys.speakers]");
+        assert_eq!(parse_err("[keys.sinks]\ndown = \"hyper-j\"\n"), "invalid key 'hyper-j' for 'down'");
+        assert_eq!(parse_err("start-view = \"mixer\"\n"), "unknown view 'mixer'");
+    }
+
+    #[test]
+    fn max_volume_out_of_range_is_rejected() {
+        let config = parse("max-volume = 100\n").unwrap();
+        assert_eq!(config.max_volume, crate::volume_from_percent(100));
+        assert_eq!(parse_err("max-volume = 0\n"), "invalid max-volume 0%, expected 1% to 1000%");
+        assert_eq!(parse_err("max-volume = 5000\n"), "invalid max-volume 5000%, expected 1% to 1000%");
+    }
+}
@@ -0,0 +1,74 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Terminal settings from before entering raw mode.
+static SAVED_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// Restores the terminal if the program panics while the interface is shown. Create it before
+/// `draw::setup_terminal`, dropping it brings back the default panic behaviour.
+///
+/// On a panic the alternate screen is left, cooked mode is restored and the cursor is shown, then a
+/// crash report with the message and a backtrace is written to the state directory and the
+/// program exits. Exiting stops the other thread from drawing on the restored terminal.
+pub struct TerminalGuard {
+}
+
+impl TerminalGuard {
+    pub fn new() -> TerminalGuard {
+        // SAFETY: termios is a plain C struct of integers and arrays, all zeroes is a valid value.
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        // SAFETY: termios is a valid, writable struct for the call; on failure it is not used.
+        if unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut termios) } == 0 {
+            *SAVED_TERMIOS.lock().unwrap() = Some(termios);
+        }
+
+        std::panic::set_hook(Box::new(|info| {
+            restore_terminal();
+
+            let report = format!("pavucontrolrs {} crashed\n\n{}\n\nbacktrace:\n{}\n",
+                                 env!("CARGO_PKG_VERSION"), info, std::backtrace::Backtrace::force_capture());
+            match write_report(&report) {
+                Some(path) => eprintln!("{}\ncrash report written to {}", info, path.display()),
+                None       => eprintln!("{}", report),
+            }
+            std::process::exit(101);
+        }));
+
+        return TerminalGuard {};
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            let _ = std::panic::take_hook();
+        }
+    }
+}
+
+/// Undo what `draw::setup_terminal` did, in case its terminal is never dropped.
+fn restore_terminal() {
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l{}{}",
+                   termion::screen::ToMainScreen, termion::cursor::Show);
+    let _ = stdout.flush();
+
+    // the lock may be poisoned if the panic happened while holding it, the settings are still fine
+    let saved = match SAVED_TERMIOS.lock() {
+        Ok(saved) => *saved,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    if let Some(termios) = saved {
+        // SAFETY: termios was filled in by tcgetattr and lives until the call returns.
+        unsafe { libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, &termios); }
+    }
+}
+
+/// Write the report to `crash-<time>.txt` in the state directory, returning its path.
+fn write_report(report: &str) -> Option<std::path::PathBuf> {
+    let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    let path = crate::log::state_dir()?.join(format!("crash-{}.txt", time));
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    std::fs::write(&path, report).ok()?;
+    return Some(path);
+}
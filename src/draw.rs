@@ -5,12 +5,15 @@ use termion::raw::IntoRawMode;
 use termion::screen::IntoAlternateScreen;
 use termion::input::TermRead;
 use ratatui::backend::TermionBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::widgets::{Block, Borders, Gauge, Widget, Tabs};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Gauge, Widget, StatefulWidget, Tabs, Paragraph};
 use ratatui::Terminal;
 
-use crate::{App, AppView};
+use crate::{App, AppView, ConnectionStatus};
+use crate::config::{Colors, Keymap, KeymapSection};
+use crate::status::StatusKind;
 use crate::views;
 
 type FinalTerminal = ratatui::terminal::Terminal<
@@ -21,6 +24,23 @@ type FinalTerminal = ratatui::terminal::Terminal<
                      std::io::Stdout
                      >>>>>;
 
+/// Something on screen that reacts to mouse clicks. The areas are recorded while drawing.
+#[derive(Clone, Copy)]
+pub enum ClickTarget {
+    Tab(AppView),
+    /// An entry of the current view, with the area of its volume gauge if there is a single one.
+    Entry { index: u32, volume_area: Option<ratatui::layout::Rect> },
+    /// A profile of a card in the card view.
+    Profile { card: u32, profile: usize },
+    /// A line in a popup, identified by the index of the sink, source or port it stands for.
+    PopupEntry(u32),
+    /// A line in the server popup.
+    ServerEntry(usize),
+}
+
+pub const TAB_VIEWS: [AppView; 6] = [AppView::SinkInputs, AppView::SourceOutputs, AppView::Sinks, AppView::Sources, AppView::Cards, AppView::Modules];
+const TAB_TITLES: [&str; 6] = ["Sink Inputs", "Source Output", "Sinks", "Sources", "Cards", "Modules"];
+
 pub fn setup_terminal() -> Result<FinalTerminal, std::io::Error> {
     let stdout = std::io::stdout().into_raw_mode()?;
     let stdout = MouseTerminal::from(stdout);
@@ -39,8 +59,13 @@ pub fn draw_frame<T: ratatui::backend::Backend>(terminal: &mut ratatui::Terminal
         AppView::Sinks         => app.sink_list.reset_changed(),
         AppView::Sources       => app.source_list.reset_changed(),
         AppView::Cards         => app.card_list.reset_changed(),
+        AppView::Modules       => app.module_list.reset_changed(),
     };
 
+    if app.meters.reset_changed() {
+        do_redraw = true;
+    }
+
     if app.redraw {
         do_redraw = true;
         app.redraw = false;
@@ -50,6 +75,8 @@ pub fn draw_frame<T: ratatui::backend::Backend>(terminal: &mut ratatui::Terminal
         return;
     }
 
+    app.click_targets.clear();
+
     let _ = terminal.draw(|f| {
 
         let chunks = Layout::default()
@@ -58,23 +85,158 @@ pub fn draw_frame<T: ratatui::backend::Backend>(terminal: &mut ratatui::Terminal
             .constraints(vec![Constraint::Length(3), Constraint::Length(1), Constraint::Min(0)])
             .split(f.size());
 
-        Tabs::new(["Sink Inputs", "Source Output", "Sinks", "Sources", "Cards"])
-            .block(Block::bordered().title(" Tabs "))
-            .highlight_style(Style::default().fg(Color::Yellow))
+        let mut tabs_block = Block::bordered().title(" Tabs ");
+        let server = match (&app.server_label, &app.server) {
+            (Some(label), Some(address)) => Some(format!(" {} ({}) ", label, address)),
+            (Some(label), None)          => Some(format!(" {} ", label)),
+            (None, Some(address))        => Some(format!(" {} ", address)),
+            (None, None)                 => None,
+        };
+        if let Some(server) = server {
+            tabs_block = tabs_block.title(Title::from(server).alignment(Alignment::Right));
+        }
+        let search = app.search.text(app.view);
+        if app.search.editing {
+            tabs_block = tabs_block.title(Title::from(format!(" /{}_ ", search)).position(Position::Bottom));
+        } else if !search.is_empty() {
+            tabs_block = tabs_block.title(Title::from(format!(" /{} ", search)).position(Position::Bottom));
+        }
+        let tabs_inner = tabs_block.inner(chunks[0]);
+        let mut x = tabs_inner.left();
+        for (view, title) in TAB_VIEWS.iter().zip(TAB_TITLES.iter()) {
+            // titles are padded by a space on each side and separated by a one column divider
+            let width = title.chars().count() as u16 + 2;
+            let area = tabs_inner.intersection(ratatui::layout::Rect { x, width, ..tabs_inner });
+            app.click_targets.push((area, ClickTarget::Tab(*view)));
+            x += width + 1;
+        }
+
+        Tabs::new(TAB_TITLES)
+            .block(tabs_block)
+            .highlight_style(Style::default().fg(app.config.colors.highlight))
             .divider(ratatui::symbols::DOT)
             .select(app.view as usize)
             .render(chunks[0], f.buffer_mut());
 
+        // the status line shows the connection state, or else the outcome of the last operation
+        let banner = match app.connection_status {
+            ConnectionStatus::Connected    => None,
+            ConnectionStatus::Connecting   => Some(" Connecting to the sound server..."),
+            ConnectionStatus::Disconnected => Some(" Disconnected from the sound server, retrying..."),
+        };
+        let status = match (banner, app.status.get()) {
+            (Some(banner), _)                       => Some((String::from(banner), app.config.colors.idle)),
+            (None, Some((text, StatusKind::Info)))  => Some((format!(" {}", text), app.config.colors.status)),
+            (None, Some((text, StatusKind::Error))) => Some((format!(" {}", text), app.config.colors.error)),
+            (None, None)                            => None,
+        };
+        if let Some((text, color)) = status {
+            Paragraph::new(text)
+                .style(Style::default().fg(color))
+                .render(chunks[1], f.buffer_mut());
+        }
+
         match app.view {
             AppView::SinkInputs    => views::sink_inputs::draw(f, chunks[2], app),
             AppView::SourceOutputs => views::source_outputs::draw(f, chunks[2], app),
             AppView::Sinks         => views::sinks::draw(f, chunks[2], app),
             AppView::Sources       => views::sources::draw(f, chunks[2], app),
             AppView::Cards         => views::cards::draw(f, chunks[2], app),
+            AppView::Modules       => views::modules::draw(f, chunks[2], app),
         };
+
+        if app.properties_popup.is_some() {
+            draw_properties_popup(f, chunks[2], app);
+        }
+
+        if let Some(selected) = app.server_popup {
+            draw_server_popup(f, chunks[2], app, selected);
+        }
     });
 }
 
+/// Draw the properties of the selected entry as a two column table, filtered and scrolled as set
+/// in `app.properties_popup`.
+fn draw_properties_popup(frame: &mut ratatui::terminal::Frame, rect: ratatui::layout::Rect, app: &mut App) {
+
+    let (name, rows) = match crate::properties::rows(app) {
+        Some(x) => x,
+        None => { app.properties_popup = None; return; }
+    };
+    let popup = match app.properties_popup.as_mut() {
+        Some(popup) => popup,
+        None => return,
+    };
+    let rows: Vec<&(String, String)> = rows.iter().filter(|(key, value)| popup.matches(key, value)).collect();
+
+    let rect = rect.inner(&Margin::new(2, 2));
+    ClearingWidget::default()
+        .render(rect, frame.buffer_mut());
+
+    let mut block = Block::bordered().title(format!(" Properties of {} ", name));
+    if popup.editing || !popup.filter.is_empty() {
+        let cursor = if popup.editing { "_" } else { "" };
+        block = block.title(Title::from(format!(" /{}{} ", popup.filter, cursor)).position(ratatui::widgets::block::Position::Bottom));
+    }
+    let inner = block.inner(rect);
+    block.render(rect, frame.buffer_mut());
+
+    popup.scroll = popup.scroll.min(rows.len().saturating_sub(inner.height as usize));
+
+    let key_width = rows.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    for (j, (key, value)) in rows.iter().skip(popup.scroll).take(inner.height as usize).enumerate() {
+        let line = ratatui::layout::Rect { y: inner.y + j as u16, height: 1, ..inner };
+        Paragraph::new(format!(" {:<width$}  {}", key, value, width = key_width))
+                .render(line, frame.buffer_mut());
+    }
+
+    if rows.len() > inner.height as usize {
+        let mut state = ratatui::widgets::ScrollbarState::new(rows.len() - inner.height as usize)
+            .position(popup.scroll)
+            .viewport_content_length(inner.height as usize);
+        ratatui::widgets::Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .render(rect, frame.buffer_mut(), &mut state);
+    }
+}
+
+/// Draw the popup listing the known servers, on top of the view.
+fn draw_server_popup(frame: &mut ratatui::terminal::Frame, rect: ratatui::layout::Rect, app: &mut App, selected: usize) {
+
+    let rect = rect.inner(&Margin::new(4, 4));
+    ClearingWidget::default()
+        .render(rect, frame.buffer_mut());
+
+    let block = Block::bordered().title(" Switch Server ");
+    let inner = block.inner(rect);
+    block.render(rect, frame.buffer_mut());
+
+    let choices = app.server_choices();
+    let list = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); choices.len()])
+        .split(inner);
+
+    for (j, server) in choices.iter().enumerate() {
+        let mut style = Style::default();
+        if selected == j {
+            style = Style::default().fg(app.config.colors.popup_cursor)
+        }
+        if *server == app.server && selected != j {
+            style = Style::default().fg(app.config.colors.popup_current)
+        }
+        app.click_targets.push((list[j], ClickTarget::ServerEntry(j)));
+        let text = match server {
+            Some(server) => format!(" {} ", server),
+            None         => String::from(" Default server "),
+        };
+        Paragraph::new(text)
+            .style(style)
+            .render(list[j], frame.buffer_mut());
+    }
+}
+
 #[derive(Default)]
 pub struct ClearingWidget {
 }
@@ -88,3 +250,449 @@ impl Widget for ClearingWidget {
         }
     }
 }
+
+/// Draw the keybinding popup of a view, generated from the keymap. The popup scrolls if it does
+/// not fit, `scroll` is clamped to the possible range.
+pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: ratatui::layout::Rect,
+                             keymap: &Keymap, section: KeymapSection, scroll: &mut usize) {
+
+    let keys = keymap.help(section);
+
+    let rect = rect.inner(&ratatui::layout::Margin::new(4, 4));
+    ClearingWidget::default()
+        .render(rect, frame.buffer_mut());
+
+    let block = Block::bordered().title(" Keybindings ");
+    let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
+                                   // the block
+    block.render(rect, frame.buffer_mut());
+
+    *scroll = (*scroll).min(keys.len().saturating_sub(inner.height as usize));
+
+    let key_width = keys.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0).max(17);
+    for (j, (key, desc)) in keys.iter().skip(*scroll).take(inner.height as usize).enumerate() {
+        let line = ratatui::layout::Rect { y: inner.y + j as u16, height: 1, ..inner };
+        Paragraph::new(format!(" {:^width$} {}", key, desc, width = key_width))
+                .render(line, frame.buffer_mut());
+    }
+
+    if keys.len() > inner.height as usize {
+        let mut state = ratatui::widgets::ScrollbarState::new(keys.len() - inner.height as usize)
+            .position(*scroll)
+            .viewport_content_length(inner.height as usize);
+        ratatui::widgets::Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .render(rect, frame.buffer_mut(), &mut state);
+    }
+}
+
+/// Draw a peak level bar over the bottom border of a bordered gauge. The peak is given as linear
+/// amplitude and drawn on the same scale as the volume gauge, so a full scale peak reaches its 100%
+/// mark.
+pub fn draw_peak_meter(buf: &mut ratatui::buffer::Buffer, rect: ratatui::layout::Rect, peak: f32, style: GaugeStyle) {
+    if rect.width < 3 || rect.height < 2 {
+        return;
+    }
+
+    let peak_volume = pulse::volume::Volume::from(pulse::volume::VolumeLinear(peak as f64));
+    let peak_ratio = peak_volume.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
+
+    let width = rect.width - 2;
+    let filled = (width as f64 * (peak_ratio / gauge_scale(style.max_volume)).min(1.0)).round() as u16;
+    let y = rect.bottom() - 1;
+    for x in rect.left() + 1 .. rect.left() + 1 + filled {
+        buf.get_mut(x, y)
+            .set_symbol(ratatui::symbols::line::THICK_HORIZONTAL)
+            .set_fg(style.colors.meter);
+    }
+}
+
+/// How many times 100% fits into a gauge scaled to `max_volume`, at least once.
+fn gauge_scale(max_volume: u32) -> f64 {
+    return (max_volume as f64 / pulse::volume::Volume::NORMAL.0 as f64).max(1.0);
+}
+
+/// How the volume gauges of an entry are drawn.
+#[derive(Clone, Copy)]
+pub struct GaugeStyle<'a> {
+    /// Volume at the right end of the gauges.
+    pub max_volume: u32,
+    pub color:      Color,
+    pub colors:     &'a Colors,
+    /// Muted gauges have "(muted)" added to their labels.
+    pub muted:      bool,
+}
+
+/// Draw an entry with one gauge per channel inside of the given block, labelled from the channel
+/// map. While the channels are unlocked, only the selected channel is highlighted.
+pub fn draw_channel_gauges(buf: &mut ratatui::buffer::Buffer, rect: ratatui::layout::Rect, block: Block,
+                           volume: &pulse::volume::ChannelVolumes, channel_map: &pulse::channelmap::Map,
+                           channels: &crate::channels::ChannelState, style: GaugeStyle) {
+    let lock_title = if channels.is_locked() { " locked " } else { " unlocked " };
+    let block = block.title(ratatui::widgets::block::Title::from(lock_title).alignment(ratatui::layout::Alignment::Right));
+
+    let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
+                                   // the block
+    block.render(rect, buf);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); volume.len() as usize])
+        .split(inner);
+
+    let selected = channels.target_channel(volume.len());
+
+    for (j, vol) in volume.get().iter().enumerate() {
+        let volume_ratio = vol.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
+        let label = format!("{}: {:.0}%", crate::channels::channel_name(channel_map, j), volume_ratio * 100f64);
+
+        let channel_color = match selected {
+            Some(s) if s != j => style.colors.inactive,
+            _                 => style.color,
+        };
+
+        draw_volume_gauge(buf, rows[j], None, volume_ratio, label, GaugeStyle { color: channel_color, ..style });
+    }
+}
+
+/// Draw one line of small sliders for the balance, fade and LFE balance of an entry, as far as
+/// they apply to its channel map.
+pub fn draw_balance_sliders(buf: &mut ratatui::buffer::Buffer, rect: ratatui::layout::Rect,
+                            volume: &pulse::volume::ChannelVolumes, channel_map: &pulse::channelmap::Map, colors: &Colors) {
+    const SLIDER_WIDTH: usize = 11;
+
+    let mut text = String::new();
+    for kind in crate::channels::BalanceKind::ALL.iter() {
+        if let Some(value) = kind.get(volume, channel_map) {
+            let (label, low, high) = kind.labels();
+            let position = ((value + 1.0) / 2.0 * (SLIDER_WIDTH - 1) as f32).round() as usize;
+            let slider: String = (0..SLIDER_WIDTH).map(|x| {
+                if x == position { '●' } else if x == SLIDER_WIDTH / 2 { '┼' } else { '─' }
+            }).collect();
+            text += &format!("  {} {} {} {}  ", label, low, slider, high);
+        }
+    }
+
+    Paragraph::new(text)
+        .style(Style::default().fg(colors.balance))
+        .render(rect, buf);
+}
+
+/// Draw a volume gauge scaled to the `max_volume` of the style. If that is above 100%, the part of the gauge above
+/// 100% is drawn in a distinct color and the 100% position is marked on the border.
+pub fn draw_volume_gauge(buf: &mut ratatui::buffer::Buffer, rect: ratatui::layout::Rect, block: Option<Block>,
+                         volume_ratio: f64, mut label: String, style: GaugeStyle) {
+
+    let GaugeStyle { max_volume, color, colors, muted } = style;
+    let scale = gauge_scale(max_volume);
+    if muted {
+        label += " (muted)";
+    }
+
+    let inner = match &block {
+        Some(block) => block.inner(rect),
+        None        => rect,
+    };
+
+    let mut gauge = Gauge::default()
+        .gauge_style(Style::default().fg(color))
+        .ratio((volume_ratio / scale).min(1.0))
+        .label(label);
+    if let Some(block) = block {
+        gauge = gauge.block(block);
+    }
+    gauge.render(rect, buf);
+
+    if scale <= 1.0 || inner.width == 0 {
+        return;
+    }
+
+    let normal_x = inner.left() + (inner.width as f64 / scale).round() as u16;
+    for y in inner.top()..inner.bottom() {
+        for x in normal_x..inner.right() {
+            let cell = buf.get_mut(x, y);
+            // filled cells carry the gauge color as foreground, the ones below the label as
+            // background
+            if cell.fg == color && cell.symbol() == ratatui::symbols::block::FULL {
+                cell.set_fg(colors.overamplified);
+            } else if cell.bg == color {
+                cell.set_bg(colors.overamplified);
+            }
+        }
+    }
+
+    if inner.top() > rect.top() {
+        buf.get_mut(normal_x, rect.top()).set_symbol(ratatui::symbols::line::HORIZONTAL_DOWN);
+    }
+    if inner.bottom() < rect.bottom() {
+        buf.get_mut(normal_x, rect.bottom() - 1).set_symbol(ratatui::symbols::line::HORIZONTAL_UP);
+    }
+}
+
+/// Lay out list entries of the given heights from top to bottom, starting at the entry at
+/// `offset`. The offset is adjusted so the selected entry is visible. Entries that do not fit
+/// completely are left out (`None`), and a scrollbar is drawn if not all entries fit.
+pub fn layout_list(buf: &mut ratatui::buffer::Buffer, rect: ratatui::layout::Rect, heights: &[u16],
+                   selected: Option<usize>, offset: &mut usize) -> Vec<Option<ratatui::layout::Rect>> {
+    let total_height: u32 = heights.iter().map(|&h| h as u32).sum();
+    let overflow = total_height > rect.height as u32;
+
+    let fits = |from: usize, to: usize| -> bool {
+        heights[from..=to].iter().map(|&h| h as u32).sum::<u32>() <= rect.height as u32
+    };
+
+    if !overflow {
+        *offset = 0;
+    } else {
+        *offset = (*offset).min(heights.len() - 1);
+        if let Some(selected) = selected {
+            if selected < *offset {
+                *offset = selected;
+            }
+            while *offset < selected && !fits(*offset, selected) {
+                *offset += 1;
+            }
+        }
+        // don't leave space at the bottom while there are entries above
+        while *offset > 0 && fits(*offset - 1, heights.len() - 1) {
+            *offset -= 1;
+        }
+    }
+
+    let list_rect = if overflow && rect.width > 1 {
+        ratatui::layout::Rect { width: rect.width - 1, ..rect }
+    } else {
+        rect
+    };
+
+    let mut y = list_rect.top();
+    let mut rects = Vec::with_capacity(heights.len());
+    for (i, &height) in heights.iter().enumerate() {
+        // the first entry is cut off instead of left out, in case it is larger than the whole list
+        let height = if i == *offset { height.min(list_rect.height) } else { height };
+        if i < *offset || y + height > list_rect.bottom() {
+            rects.push(None);
+            continue;
+        }
+        rects.push(Some(ratatui::layout::Rect { y, height, ..list_rect }));
+        y += height;
+    }
+
+    if overflow {
+        let position: u32 = heights[..*offset].iter().map(|&h| h as u32).sum();
+        let mut state = ratatui::widgets::ScrollbarState::new(total_height.saturating_sub(rect.height as u32) as usize)
+            .position(position as usize)
+            .viewport_content_length(rect.height as usize);
+        ratatui::widgets::Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .render(rect, buf, &mut state);
+    }
+
+    return rects;
+}
+
+/// Volume corresponding to a click position on a gauge drawn by `draw_volume_gauge`.
+pub fn volume_at(area: ratatui::layout::Rect, column: u16, max_volume: u32) -> u32 {
+    let scale = (max_volume as f64 / pulse::volume::Volume::NORMAL.0 as f64).max(1.0);
+    let position = column.saturating_sub(area.left()) as f64 / (area.width.max(2) - 1) as f64;
+    return (position.min(1.0) * scale * pulse::volume::Volume::NORMAL.0 as f64).round() as u32;
+}
+
+/// Snapshots of every view and popup. Review changes with `cargo insta review`.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use crate::test_util::{test_app, test_card, test_module, test_sink, test_sink_input, test_source, test_source_output};
+    use crate::{ConnectionStatus, PortInfo};
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.connection_status = ConnectionStatus::Connected;
+        app.default_sink_name = Some(String::from("speakers"));
+        app.default_source_name = Some(String::from("microphone"));
+
+        let mut speakers = test_sink(1, "speakers");
+        speakers.ports = vec![
+            PortInfo {
+                name:        String::from("analog-output-speaker"),
+                description: String::from("Speakers"),
+                priority:    100,
+                available:   pulse::def::PortAvailable::Unknown,
+            },
+            PortInfo {
+                name:        String::from("analog-output-headphones"),
+                description: String::from("Headphones"),
+                priority:    200,
+                available:   pulse::def::PortAvailable::No,
+            },
+        ];
+        speakers.active_port_index = Some(0);
+        app.sink_list.update(1, speakers);
+        app.source_list.update(3, test_source(3, "microphone", None));
+        app.source_list.update(4, test_source(4, "speakers monitor", Some(1)));
+        app.sink_input_list.update(10, test_sink_input(10, "music", 1));
+        app.sink_input_list.update(11, test_sink_input(11, "call", 2));
+        app.source_output_list.update(20, test_source_output(20, "recorder", 3));
+        app.card_list.update(30, test_card(30, "builtin", &["Analog Stereo Duplex", "Off"]));
+        app.module_list.update(40, test_module(40, "module-alsa-card", Some("device_id=\"0\" name=\"pci-0000_00_1f.3\"")));
+        app.module_list.update(41, test_module(41, "module-null-sink", None));
+        return app;
+    }
+
+    fn render(app: &mut App) -> TestBackend {
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        app.redraw = true;
+        draw_frame(&mut terminal, app);
+        return terminal.backend().clone();
+    }
+
+    #[test]
+    fn sink_inputs() {
+        let mut app = app();
+        app.view = AppView::SinkInputs;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn source_outputs() {
+        let mut app = app();
+        app.view = AppView::SourceOutputs;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn sinks() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn sources() {
+        let mut app = app();
+        app.view = AppView::Sources;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn cards() {
+        let mut app = app();
+        app.view = AppView::Cards;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn modules() {
+        let mut app = app();
+        app.view = AppView::Modules;
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn sink_popup() {
+        let mut app = app();
+        app.view = AppView::SinkInputs;
+        let stream = app.sink_input_list.get_selected().unwrap();
+        app.sink_input_view_data.open_sink_popup(stream);
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn source_popup() {
+        let mut app = app();
+        app.view = AppView::SourceOutputs;
+        let stream = app.source_output_list.get_selected().unwrap();
+        app.source_output_view_data.open_source_popup(stream);
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn port_popup() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        let sink = app.sink_list.get_selected().unwrap();
+        app.sink_view_data.open_port_popup(sink);
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn keybinding_popup() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        app.sink_view_data.open_keybinding_popup();
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn properties_popup() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        app.properties_popup = Some(Default::default());
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn search() {
+        let mut app = app();
+        app.view = AppView::SinkInputs;
+        app.search.start(AppView::SinkInputs);
+        for c in "CALL\n".chars() {
+            crate::search::handle_key_event(Key::Char(c), &mut app);
+        }
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn grouped_streams() {
+        let mut app = app();
+        app.view = AppView::SinkInputs;
+        app.group_streams = true;
+        for index in [12, 13].iter() {
+            let mut stream = test_sink_input(*index, "video", 1);
+            stream.proplist.set_str("application.name", "Firefox").unwrap();
+            app.sink_input_list.update(*index, stream);
+        }
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn muted_sinks() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        for (index, name) in [(1, "speakers"), (2, "headset")].iter() {
+            let mut sink = test_sink(*index, name);
+            sink.mute = true;
+            app.sink_list.update(*index, sink);
+        }
+        // the selected sink is drawn with one gauge per channel, the other one with a single gauge
+        app.sink_list.select(1);
+        crate::views::sinks::handle_key_event(termion::event::Key::Char('c'), &mut app, &mut crate::backend::FakeBackend::default());
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn full_scale_peak() {
+        let mut app = app();
+        app.view = AppView::Sinks;
+        // the monitor of the speakers, peaking at 0 dB, which is drawn up to the 100% mark
+        app.meters.set_source_peak(1, 1.0);
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn status_line() {
+        let mut app = app();
+        app.status.error(String::from("Unloaded module #101 failed: Access denied"));
+        insta::assert_snapshot!(render(&mut app));
+    }
+
+    #[test]
+    fn disconnected_banner() {
+        let mut app = app();
+        app.connection_status = ConnectionStatus::Disconnected;
+        insta::assert_snapshot!(render(&mut app));
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::{HashMap, HashSet};
+
+use pulse::proplist::Proplist;
+
+/// What streams are grouped by: the binary, or else the application name.
+pub fn group_key(proplist: &Proplist) -> Option<String> {
+    return proplist.get_str("application.process.binary").or_else(|| proplist.get_str("application.name"));
+}
+
+/// A row of a stream view, either a single stream or the streams of one application collapsed
+/// into one.
+pub struct StreamRow {
+    pub members: Vec<u32>,
+    /// Name of the application, for a collapsed group of more than one stream.
+    pub group:   Option<String>,
+}
+
+impl StreamRow {
+    pub fn contains(&self, index: u32) -> bool {
+        return self.members.contains(&index);
+    }
+}
+
+/// Groups of a stream view that are expanded, i.e. show their streams one by one.
+#[derive(Default)]
+pub struct Groups {
+    expanded: HashSet<String>,
+}
+
+impl Groups {
+    pub fn toggle_expanded(&mut self, key: String) {
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+    }
+
+    /// Rows for the given streams in order. Without `grouped` every stream is a row of its own,
+    /// otherwise streams of the same application are collapsed into the row of the first one.
+    pub fn rows<'a, I>(&self, grouped: bool, streams: I) -> Vec<StreamRow>
+        where I: Iterator<Item = (u32, &'a Proplist)> {
+        let mut rows: Vec<StreamRow> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+
+        for (index, proplist) in streams {
+            let key = match group_key(proplist) {
+                Some(key) if grouped && !self.expanded.contains(&key) => key,
+                _ => {
+                    rows.push(StreamRow { members: vec![index], group: None });
+                    continue;
+                }
+            };
+            if let Some(position) = positions.get(&key) {
+                rows[*position].members.push(index);
+                continue;
+            }
+            let name = proplist.get_str("application.name").unwrap_or_else(|| key.clone());
+            positions.insert(key, rows.len());
+            rows.push(StreamRow { members: vec![index], group: Some(name) });
+        }
+
+        // an application with a single stream is shown like any other stream
+        for row in rows.iter_mut() {
+            if row.members.len() == 1 {
+                row.group = None;
+            }
+        }
+        return rows;
+    }
+}
+
+/// The row of a stream.
+pub fn row_of(rows: &[StreamRow], index: u32) -> Option<&StreamRow> {
+    return rows.iter().find(|x| x.contains(index));
+}
+
+/// The stream to select for moving one row down or up from the row of the given stream.
+pub fn next_row(rows: &[StreamRow], index: u32, forward: bool) -> Option<u32> {
+    let position = rows.iter().position(|x| x.contains(index))?;
+    let next = if forward {
+        rows.get(position + 1)?
+    } else {
+        rows.get(position.checked_sub(1)?)?
+    };
+    return Some(next.members[0]);
+}
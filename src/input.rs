@@ -0,0 +1,62 @@
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::thread;
+
+use pulse::mainloop::api::Mainloop as _;
+use pulse::mainloop::events::io::{IoEvent, FlagSet as IoEventFlagSet};
+use pulse::mainloop::standard::Mainloop;
+use termion::event::Event;
+use termion::input::TermRead;
+
+type MainloopInner = <Mainloop as pulse::mainloop::api::Mainloop>::MI;
+
+/// Terminal input, read on its own thread and handed to the main thread. Each event also writes a
+/// byte to a socket watched by the PA mainloop, so the main thread can sleep in poll until either
+/// the server or the user does something.
+pub struct Input {
+    receiver:  mpsc::Receiver<Event>,
+    _io_event: IoEvent<MainloopInner>,
+}
+
+impl Input {
+    pub fn spawn(mainloop: &mut Mainloop) -> io::Result<Input> {
+        let (mut wake_write, wake_read) = UnixStream::pair()?;
+        wake_read.set_nonblocking(true)?;
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for evt in io::stdin().events().flatten() {
+                if sender.send(evt).is_err() {
+                    return;
+                }
+                let _ = wake_write.write(&[0]);
+            }
+        });
+
+        let fd = wake_read.as_raw_fd();
+        let io_event = mainloop.new_io_event(fd, IoEventFlagSet::INPUT, Box::new({
+            let mut wake_read = wake_read;
+            move |_, _, _| {
+                // only drain the socket, the events are taken from the channel after dispatching
+                let mut buf = [0u8; 64];
+                while let Ok(n) = wake_read.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                }
+            }
+        }));
+
+        match io_event {
+            Some(io_event) => return Ok(Input { receiver, _io_event: io_event }),
+            None => return Err(io::Error::other("failed to watch terminal input")),
+        }
+    }
+
+    /// The next event read from the terminal, if there is one.
+    pub fn try_recv(&self) -> Option<Event> {
+        return self.receiver.try_recv().ok();
+    }
+}
@@ -0,0 +1,174 @@
+use serde_json::{json, Map, Value};
+
+use pulse::channelmap;
+use pulse::proplist::Proplist;
+use pulse::volume::ChannelVolumes;
+
+use crate::{CardEntry, PortInfo, ProfileInfo, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+
+/// Machine readable representation of the entries, for `--json` and `watch`.
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+fn percent(volume: pulse::volume::Volume) -> u32 {
+    return (volume.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64 * 100f64).round() as u32;
+}
+
+fn volume(volume: &ChannelVolumes, channel_map: &channelmap::Map) -> Value {
+    let channels: Vec<Value> = volume.get().iter().enumerate()
+        .map(|(i, v)| json!({
+            "channel": crate::channels::channel_name(channel_map, i),
+            "percent": percent(*v),
+        }))
+        .collect();
+    return json!({
+        "percent":  percent(volume.avg()),
+        "channels": channels,
+    });
+}
+
+fn proplist(proplist: &Proplist) -> Value {
+    let mut map = Map::new();
+    for key in proplist.iter() {
+        if let Some(value) = proplist.get_str(&key) {
+            map.insert(key, Value::String(value));
+        }
+    }
+    return Value::Object(map);
+}
+
+pub fn sink_state(state: pulse::def::SinkState) -> &'static str {
+    match state {
+        pulse::def::SinkState::Running   => "running",
+        pulse::def::SinkState::Idle      => "idle",
+        pulse::def::SinkState::Suspended => "suspended",
+        pulse::def::SinkState::Invalid   => "invalid",
+    }
+}
+
+pub fn source_state(state: pulse::def::SourceState) -> &'static str {
+    match state {
+        pulse::def::SourceState::Running   => "running",
+        pulse::def::SourceState::Idle      => "idle",
+        pulse::def::SourceState::Suspended => "suspended",
+        pulse::def::SourceState::Invalid   => "invalid",
+    }
+}
+
+impl ToJson for PortInfo {
+    fn to_json(&self) -> Value {
+        let available = match self.available {
+            pulse::def::PortAvailable::Yes     => "yes",
+            pulse::def::PortAvailable::No      => "no",
+            pulse::def::PortAvailable::Unknown => "unknown",
+        };
+        return json!({
+            "name":        self.name,
+            "description": self.description,
+            "priority":    self.priority,
+            "available":   available,
+        });
+    }
+}
+
+impl ToJson for ProfileInfo {
+    fn to_json(&self) -> Value {
+        return json!({
+            "name":        self.name,
+            "description": self.description,
+            "n_sinks":     self.n_sinks,
+            "n_sources":   self.n_sources,
+            "priority":    self.priority,
+            "available":   self.available,
+        });
+    }
+}
+
+impl ToJson for SinkEntry {
+    fn to_json(&self) -> Value {
+        return json!({
+            "index":          self.index,
+            "name":           self.name,
+            "description":    self.description,
+            "volume":         volume(&self.volume, &self.channel_map),
+            "mute":           self.mute,
+            "state":          sink_state(self.state),
+            "owner_module":   self.owner_module,
+            "monitor_source": self.monitor_source,
+            "ports":          self.ports.iter().map(|x| x.to_json()).collect::<Vec<Value>>(),
+            "active_port":    self.active_port().map(|x| x.name.clone()),
+            "proplist":       proplist(&self.proplist),
+        });
+    }
+}
+
+impl ToJson for SourceEntry {
+    fn to_json(&self) -> Value {
+        return json!({
+            "index":           self.index,
+            "name":            self.name,
+            "description":     self.description,
+            "volume":          volume(&self.volume, &self.channel_map),
+            "mute":            self.mute,
+            "state":           source_state(self.state),
+            "owner_module":    self.owner_module,
+            "monitor_of_sink": self.monitor_of_sink,
+            "ports":           self.ports.iter().map(|x| x.to_json()).collect::<Vec<Value>>(),
+            "active_port":     self.active_port().map(|x| x.name.clone()),
+            "proplist":        proplist(&self.proplist),
+        });
+    }
+}
+
+impl ToJson for SinkInputEntry {
+    fn to_json(&self) -> Value {
+        return json!({
+            "index":           self.index,
+            "name":            self.name,
+            "display_name":    self.display_name(),
+            "sink":            self.sink_index,
+            "volume":          volume(&self.volume, &self.channel_map),
+            "mute":            self.mute,
+            "corked":          self.corked,
+            "has_volume":      self.has_volume,
+            "volume_writable": self.volume_writable,
+            "proplist":        proplist(&self.proplist),
+        });
+    }
+}
+
+impl ToJson for SourceOutputEntry {
+    fn to_json(&self) -> Value {
+        return json!({
+            "index":           self.index,
+            "name":            self.name,
+            "display_name":    self.display_name(),
+            "source":          self.source_index,
+            "volume":          volume(&self.volume, &self.channel_map),
+            "mute":            self.mute,
+            "corked":          self.corked,
+            "has_volume":      self.has_volume,
+            "volume_writable": self.volume_writable,
+            "proplist":        proplist(&self.proplist),
+        });
+    }
+}
+
+impl ToJson for CardEntry {
+    fn to_json(&self) -> Value {
+        return json!({
+            "index":          self.index,
+            "name":           self.name,
+            "description":    self.display_name(),
+            "ports":          self.ports.iter().map(|x| x.to_json()).collect::<Vec<Value>>(),
+            "profiles":       self.profiles.iter().map(|x| x.to_json()).collect::<Vec<Value>>(),
+            "active_profile": self.active_profile_index.map(|i| self.profiles[i].name.clone()),
+            "proplist":       proplist(&self.proplist),
+        });
+    }
+}
+
+pub fn list<T: ToJson>(entries: &[T]) -> Value {
+    return Value::Array(entries.iter().map(|x| x.to_json()).collect());
+}
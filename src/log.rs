@@ -0,0 +1,43 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TO_FILE: AtomicBool = AtomicBool::new(false);
+
+/// `$XDG_STATE_HOME/pavucontrolrs`, usually `~/.local/state/pavucontrolrs`.
+pub fn state_dir() -> Option<PathBuf> {
+    let state_home = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("state"),
+    };
+    return Some(state_home.join("pavucontrolrs"));
+}
+
+pub fn log_path() -> Option<PathBuf> {
+    return Some(state_dir()?.join("pavucontrolrs.log"));
+}
+
+/// Send warnings to the log file instead of stderr, which is hidden behind the interface.
+pub fn log_to_file() {
+    TO_FILE.store(true, Ordering::Relaxed);
+}
+
+/// Report something that went wrong but does not stop the program, like skipping an object the
+/// server described incompletely.
+pub fn warn(message: &str) {
+    if !TO_FILE.load(Ordering::Relaxed) {
+        eprintln!("warning: {}", message);
+        return;
+    }
+
+    let path = match log_path() {
+        Some(path) => path,
+        None => return,
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "warning: {}", message);
+    }
+}
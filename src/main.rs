@@ -7,6 +7,7 @@ extern crate libpulse_binding as pulse;
 // mod event;
 
 use std::io;
+use std::convert::TryFrom;
 
 use std::thread;
 use std::sync::atomic;
@@ -20,13 +21,11 @@ use pulse::context::introspect;
 // use pulse::stream::Stream;
 use pulse::proplist::Proplist;
 use pulse::mainloop::standard::IterateResult;
-use pulse::callbacks::ListResult;
 use pulse::def::Retval;
 use pulse::error::PAErr;
-use pulse::context::subscribe::{Facility, Operation, InterestMaskSet};
 use pulse::volume::{VolumeLinear, ChannelVolumes};
 
-use termion::event::Key;
+use termion::event::{Event, Key, MouseButton, MouseEvent};
 use termion::input::TermRead;
 
 //use byteorder::{NativeEndian, WriteBytesExt};
@@ -41,39 +40,80 @@ use draw::{draw_frame, setup_terminal};
 
 mod views;
 
+mod meters;
+
+mod channels;
+
+mod config;
+
+mod cli;
+
+mod json;
+
+mod backend;
+
+mod input;
+
+mod log;
+
+mod crash;
+
+mod status;
+
+mod properties;
+
+mod search;
+
+mod groups;
+
+mod naming;
+
+mod sorting;
+
+#[cfg(test)]
+mod test_util;
+
 
 pub const VOLUME_STEP_SMALL: u32 = 655;
 pub const VOLUME_STEP_BIG: u32 = 6554;
 
+/// Default ceiling for volume increases, in percent, matching pavucontrol.
+pub const DEFAULT_MAX_VOLUME_PERCENT: u32 = 150;
+
+/// Accepted ceilings for volume increases, in percent.
+pub const MAX_VOLUME_PERCENT_RANGE: std::ops::RangeInclusive<u32> = 1..=1000;
+
+pub const APPLICATION_ID: &str = "org.pavucontrolrs";
+
+
+/// Whether a stream belongs to this client, like the peak detecting streams of the level meters.
+fn is_own_stream(proplist: &Proplist) -> bool {
+    return proplist.get_str(pulse::proplist::properties::APPLICATION_ID).as_deref() == Some(APPLICATION_ID);
+}
+
 
 pub struct SinkInputEntry {
     index:           u32,
     name:            String,
     sink_index:      u32,
     volume:          ChannelVolumes,
+    channel_map:     pulse::channelmap::Map,
     mute:            bool,
     corked:          bool,
     has_volume:      bool,
     volume_writable: bool,
     proplist:        pulse::proplist::Proplist,
+    owner_module:    Option<u32>,
+    client:          Option<u32>,
+    sample_spec:     pulse::sample::Spec,
+    buffer_latency:  pulse::time::MicroSeconds,
+    latency:         pulse::time::MicroSeconds,
+    driver:          Option<String>,
 }
 
 impl SinkInputEntry {
     fn display_name(&self) -> String {
-        let mut name = String::from(&self.name);
-        if let Some(app_name) = self.proplist.get_str("application.name") {
-            name = format!("{} [{}]", name, app_name);
-        }
-
-        // if Some("firefox") == self.proplist.get_str("application.process.binary").as_ref().map(|x| &x[..]) {
-        //         name = format!("{} [{}]", self.name, "firefox");
-        // }
-        if let Some(bin_name) = self.proplist.get_str("application.process.binary") {
-            if bin_name == "firefox" {
-                name = format!("{} [{}]", self.name, "firefox");
-            }
-        }
-        return name;
+        return naming::display_name(&self.name, &self.proplist);
     }
 }
 
@@ -81,14 +121,21 @@ impl From<&introspect::SinkInputInfo<'_>> for SinkInputEntry {
     fn from(entry: &introspect::SinkInputInfo) -> SinkInputEntry {
         SinkInputEntry {
             index:           entry.index,
-            name:            String::from(entry.name.as_ref().expect("SinkInputInfo without name").as_ref()),
+            name:            info_string(&entry.name).unwrap_or_else(|| format!("Stream #{}", entry.index)),
             sink_index:      entry.sink,
             volume:          entry.volume,
+            channel_map:     entry.channel_map,
             mute:            entry.mute,
             corked:          entry.corked,
             has_volume:      entry.has_volume,
             volume_writable: entry.volume_writable,
             proplist:        entry.proplist.clone(),
+            owner_module:    entry.owner_module,
+            client:          entry.client,
+            sample_spec:     entry.sample_spec,
+            buffer_latency:  entry.buffer_usec,
+            latency:         entry.sink_usec,
+            driver:          info_string(&entry.driver),
         }
     }
 }
@@ -98,20 +145,23 @@ pub struct SourceOutputEntry {
     name:            String,
     source_index:    u32,
     volume:          ChannelVolumes,
+    channel_map:     pulse::channelmap::Map,
     mute:            bool,
     corked:          bool,
     has_volume:      bool,
     volume_writable: bool,
     proplist:        pulse::proplist::Proplist,
+    owner_module:    Option<u32>,
+    client:          Option<u32>,
+    sample_spec:     pulse::sample::Spec,
+    buffer_latency:  pulse::time::MicroSeconds,
+    latency:         pulse::time::MicroSeconds,
+    driver:          Option<String>,
 }
 
 impl SourceOutputEntry {
     fn display_name(&self) -> String {
-        let mut name = String::from(&self.name);
-        if let Some(app_name) = self.proplist.get_str("application.name") {
-            name = format!("{} [{}]", name, app_name);
-        }
-        return name;
+        return naming::display_name(&self.name, &self.proplist);
     }
 }
 
@@ -119,48 +169,75 @@ impl From<&introspect::SourceOutputInfo<'_>> for SourceOutputEntry {
     fn from(entry: &introspect::SourceOutputInfo) -> SourceOutputEntry {
         SourceOutputEntry {
             index:           entry.index,
-            name:            String::from(entry.name.as_ref().expect("SourceOutputInfo without name").as_ref()),
+            name:            info_string(&entry.name).unwrap_or_else(|| format!("Stream #{}", entry.index)),
             source_index:    entry.source,
             volume:          entry.volume,
+            channel_map:     entry.channel_map,
             mute:            entry.mute,
             corked:          entry.corked,
             has_volume:      entry.has_volume,
             volume_writable: entry.volume_writable,
             proplist:        entry.proplist.clone(),
+            owner_module:    entry.owner_module,
+            client:          entry.client,
+            sample_spec:     entry.sample_spec,
+            buffer_latency:  entry.buffer_usec,
+            latency:         entry.source_usec,
+            driver:          info_string(&entry.driver),
         }
     }
 }
 
 pub struct SinkEntry {
-    index:        u32,
-    name:         String,
-    description:  String,
-    volume:       ChannelVolumes,
-    mute:         bool,
-    state:        pulse::def::SinkState,
-    owner_module: Option<u32>,
-    proplist:     pulse::proplist::Proplist,
-    ports:        Vec<PortInfo>,
+    index:          u32,
+    name:           String,
+    description:    String,
+    volume:         ChannelVolumes,
+    channel_map:    pulse::channelmap::Map,
+    mute:           bool,
+    state:          pulse::def::SinkState,
+    owner_module:   Option<u32>,
+    monitor_source: u32,
+    proplist:       pulse::proplist::Proplist,
+    ports:          Vec<PortInfo>,
+    active_port_index: Option<usize>,
+    sample_spec:    pulse::sample::Spec,
+    latency:        pulse::time::MicroSeconds,
+    driver:         Option<String>,
+    card:           Option<u32>,
 }
 
 impl SinkEntry {
     fn display_name(&self) -> String {
         return String::from(&self.description);
     }
+
+    fn active_port(&self) -> Option<&PortInfo> {
+        return self.ports.get(self.active_port_index?);
+    }
 }
 
 impl From<&introspect::SinkInfo<'_>> for SinkEntry {
     fn from(entry: &introspect::SinkInfo) -> SinkEntry {
+        let name = info_string(&entry.name).unwrap_or_else(|| format!("sink-{}", entry.index));
+        let ports = convert_all(entry.ports.iter(), &name);
         SinkEntry {
-            index:        entry.index,
-            name:         String::from(entry.name.as_ref().expect("SinkInfo without name").as_ref()),
-            description:  String::from(entry.description.as_ref().expect("SinkInfo without description").as_ref()),
-            volume:       entry.volume,
-            mute:         entry.mute,
-            state:        entry.state,
-            owner_module: entry.owner_module,
-            proplist:     entry.proplist.clone(),
-            ports:        entry.ports.iter().map(|x| PortInfo::from(x)).collect(),
+            index:          entry.index,
+            description:    info_string(&entry.description).unwrap_or_else(|| name.clone()),
+            name:           name,
+            volume:         entry.volume,
+            channel_map:    entry.channel_map,
+            mute:           entry.mute,
+            state:          entry.state,
+            owner_module:   entry.owner_module,
+            monitor_source: entry.monitor_source,
+            proplist:       entry.proplist.clone(),
+            active_port_index: active_port_index(&ports, entry.active_port.as_ref().and_then(|x| x.name.as_ref())),
+            ports:          ports,
+            sample_spec:    entry.sample_spec,
+            latency:        entry.latency,
+            driver:         info_string(&entry.driver),
+            card:           entry.card,
         }
     }
 }
@@ -170,12 +247,18 @@ pub struct SourceEntry {
     name:            String,
     description:     String,
     volume:          ChannelVolumes,
+    channel_map:     pulse::channelmap::Map,
     mute:            bool,
     monitor_of_sink: Option<u32>,
     state:           pulse::def::SourceState,
     owner_module:    Option<u32>,
     proplist:        pulse::proplist::Proplist,
     ports:           Vec<PortInfo>,
+    active_port_index: Option<usize>,
+    sample_spec:     pulse::sample::Spec,
+    latency:         pulse::time::MicroSeconds,
+    driver:          Option<String>,
+    card:            Option<u32>,
 }
 
 impl SourceEntry {
@@ -186,21 +269,33 @@ impl SourceEntry {
     fn is_monitor(&self) -> bool {
         return self.monitor_of_sink != None;
     }
+
+    fn active_port(&self) -> Option<&PortInfo> {
+        return self.ports.get(self.active_port_index?);
+    }
 }
 
 impl From<&introspect::SourceInfo<'_>> for SourceEntry {
     fn from(entry: &introspect::SourceInfo) -> SourceEntry {
+        let name = info_string(&entry.name).unwrap_or_else(|| format!("source-{}", entry.index));
+        let ports = convert_all(entry.ports.iter(), &name);
         SourceEntry {
             index:           entry.index,
-            name:            String::from(entry.name.as_ref().expect("SourceInfo without name").as_ref()),
-            description:     String::from(entry.description.as_ref().expect("SourceInfo without description").as_ref()),
+            description:     info_string(&entry.description).unwrap_or_else(|| name.clone()),
+            name:            name,
             volume:          entry.volume,
+            channel_map:     entry.channel_map,
             mute:            entry.mute,
             monitor_of_sink: entry.monitor_of_sink,
             state:           entry.state,
             owner_module:    entry.owner_module,
             proplist:        entry.proplist.clone(),
-            ports:           entry.ports.iter().map(|x| PortInfo::from(x)).collect(),
+            active_port_index: active_port_index(&ports, entry.active_port.as_ref().and_then(|x| x.name.as_ref())),
+            ports:           ports,
+            sample_spec:     entry.sample_spec,
+            latency:         entry.latency,
+            driver:          info_string(&entry.driver),
+            card:            entry.card,
         }
     }
 }
@@ -213,6 +308,8 @@ pub struct CardEntry {
     profiles:               Vec<ProfileInfo>,
     active_profile_index:   Option<usize>,
     selected_profile_index: Option<usize>,
+    owner_module:           Option<u32>,
+    driver:                 Option<String>,
 
     // active_profile_name: Option<String>,
 }
@@ -229,18 +326,21 @@ impl CardEntry {
 impl From<&introspect::CardInfo<'_>> for CardEntry {
     fn from(entry: &introspect::CardInfo) -> CardEntry {
 
-        let active_index = entry.active_profile.as_ref().map(|active_profile| {
-            let active_name = active_profile.name.as_ref().expect("Active profile without name");
-            let active_index = entry.profiles.iter().position(|p| p.name.as_ref().expect("Card profile without name") == active_name.as_ref());
-            active_index.expect("Active card profile not found in profile list")
-        });
+        let name = info_string(&entry.name).unwrap_or_else(|| format!("card-{}", entry.index));
+        let profiles: Vec<ProfileInfo> = convert_all(entry.profiles.iter(), &name);
+
+        // a card without a known active profile is shown with none selected
+        let active_name = entry.active_profile.as_ref().and_then(|x| x.name.as_ref());
+        let active_index = active_name.and_then(|active_name| profiles.iter().position(|p| p.name == *active_name));
 
         CardEntry {
             index:                  entry.index,
-            name:                   String::from(entry.name.as_ref().expect("CardInfo without name").as_ref()),
             proplist:               entry.proplist.clone(),
-            ports:                  entry.ports.iter().map(|x| PortInfo::from(x)).collect(),
-            profiles:               entry.profiles.iter().map(|x| ProfileInfo::from(x)).collect(),
+            ports:                  convert_all(entry.ports.iter(), &name),
+            profiles:               profiles,
+            name:                   name,
+            owner_module:           entry.owner_module,
+            driver:                 info_string(&entry.driver),
             active_profile_index:   active_index,
             selected_profile_index: active_index,
             // active_profile_name: entry.active_profile.as_ref().map(|x| String::from(x.name.as_ref().expect("Active profile without name").as_ref())),
@@ -248,6 +348,33 @@ impl From<&introspect::CardInfo<'_>> for CardEntry {
     }
 }
 
+pub struct ModuleEntry {
+    index:    u32,
+    name:     String,
+    argument: Option<String>,
+    /// Usage counter, `None` if the module does not keep one.
+    n_used:   Option<u32>,
+    proplist: pulse::proplist::Proplist,
+}
+
+impl ModuleEntry {
+    fn display_name(&self) -> String {
+        return String::from(&self.name);
+    }
+}
+
+impl From<&introspect::ModuleInfo<'_>> for ModuleEntry {
+    fn from(entry: &introspect::ModuleInfo) -> ModuleEntry {
+        ModuleEntry {
+            index:    entry.index,
+            name:     info_string(&entry.name).unwrap_or_else(|| format!("module-{}", entry.index)),
+            argument: info_string(&entry.argument).filter(|x| !x.is_empty()),
+            n_used:   entry.n_used,
+            proplist: entry.proplist.clone(),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct PortInfo {
     name:        String,
@@ -257,36 +384,59 @@ struct PortInfo {
     // additional for CardPorts: direction, proplist, latency_offset, profiles
 }
 
-impl From<&introspect::SinkPortInfo<'_>> for PortInfo {
-    fn from(info: &introspect::SinkPortInfo) -> PortInfo {
-        PortInfo {
-            name:        String::from(info.name.as_ref().expect("SinkPortInfo without name").as_ref()),
-            description: String::from(info.description.as_ref().expect("SinkPortInfo without description").as_ref()),
+impl PortInfo {
+    fn display_name(&self) -> &str {
+        return &self.description;
+    }
+
+    fn availability_label(&self) -> &'static str {
+        match self.available {
+            pulse::def::PortAvailable::Yes     => "plugged in",
+            pulse::def::PortAvailable::No      => "unplugged",
+            pulse::def::PortAvailable::Unknown => "",
+        }
+    }
+}
+
+impl TryFrom<&introspect::SinkPortInfo<'_>> for PortInfo {
+    type Error = &'static str;
+
+    fn try_from(info: &introspect::SinkPortInfo) -> Result<PortInfo, Self::Error> {
+        let name = info_string(&info.name).ok_or("port without name")?;
+        return Ok(PortInfo {
+            description: info_string(&info.description).unwrap_or_else(|| name.clone()),
+            name:        name,
             priority:    info.priority,
             available:   info.available,
-        }
+        });
     }
 }
 
-impl From<&introspect::SourcePortInfo<'_>> for PortInfo {
-    fn from(info: &introspect::SourcePortInfo) -> PortInfo {
-        PortInfo {
-            name:        String::from(info.name.as_ref().expect("SourcePortInfo without name").as_ref()),
-            description: String::from(info.description.as_ref().expect("SourcePortInfo without description").as_ref()),
+impl TryFrom<&introspect::SourcePortInfo<'_>> for PortInfo {
+    type Error = &'static str;
+
+    fn try_from(info: &introspect::SourcePortInfo) -> Result<PortInfo, Self::Error> {
+        let name = info_string(&info.name).ok_or("port without name")?;
+        return Ok(PortInfo {
+            description: info_string(&info.description).unwrap_or_else(|| name.clone()),
+            name:        name,
             priority:    info.priority,
             available:   info.available,
-        }
+        });
     }
 }
 
-impl From<&introspect::CardPortInfo<'_>> for PortInfo {
-    fn from(info: &introspect::CardPortInfo) -> PortInfo {
-        PortInfo {
-            name:        String::from(info.name.as_ref().expect("CardPortInfo without name").as_ref()),
-            description: String::from(info.description.as_ref().expect("CardPortInfo without description").as_ref()),
+impl TryFrom<&introspect::CardPortInfo<'_>> for PortInfo {
+    type Error = &'static str;
+
+    fn try_from(info: &introspect::CardPortInfo) -> Result<PortInfo, Self::Error> {
+        let name = info_string(&info.name).ok_or("port without name")?;
+        return Ok(PortInfo {
+            description: info_string(&info.description).unwrap_or_else(|| name.clone()),
+            name:        name,
             priority:    info.priority,
             available:   info.available,
-        }
+        });
     }
 }
 
@@ -306,27 +456,71 @@ impl ProfileInfo {
     }
 }
 
-impl From<&introspect::CardProfileInfo<'_>> for ProfileInfo {
-    fn from(info: &introspect::CardProfileInfo) -> ProfileInfo {
-        ProfileInfo {
-            name:        String::from(info.name.as_ref().expect("CardProfileInfo2 without name").as_ref()),
-            description: String::from(info.description.as_ref().expect("CardProfileInfo2 without description").as_ref()),
+impl TryFrom<&introspect::CardProfileInfo<'_>> for ProfileInfo {
+    type Error = &'static str;
+
+    fn try_from(info: &introspect::CardProfileInfo) -> Result<ProfileInfo, Self::Error> {
+        let name = info_string(&info.name).ok_or("profile without name")?;
+        return Ok(ProfileInfo {
+            description: info_string(&info.description).unwrap_or_else(|| name.clone()),
+            name:        name,
             priority:    info.priority,
             n_sinks:     info.n_sinks,
             n_sources:   info.n_sources,
             available:   info.available,
+        });
+    }
+}
+
+/// Copy an optional string of a server object. Some servers leave out fields like descriptions,
+/// PipeWire does so for some nodes.
+fn info_string(value: &Option<std::borrow::Cow<str>>) -> Option<String> {
+    return value.as_ref().map(|x| String::from(x.as_ref()));
+}
+
+/// Convert the ports or profiles of an object, skipping the ones that can not be used.
+fn convert_all<I, T>(items: I, owner: &str) -> Vec<T>
+    where I: Iterator, T: TryFrom<I::Item, Error = &'static str> {
+    let mut result = Vec::new();
+    for item in items {
+        match T::try_from(item) {
+            Ok(x) => result.push(x),
+            Err(e) => log::warn(&format!("{}: skipping {}", owner, e)),
         }
     }
+    return result;
+}
+
+fn active_port_index(ports: &[PortInfo], active_name: Option<&std::borrow::Cow<str>>) -> Option<usize> {
+    let active_name = active_name?;
+    return ports.iter().position(|p| p.name == *active_name);
 }
 
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 enum AppView {
     SinkInputs,
     SourceOutputs,
     Sinks,
     Sources,
     Cards,
+    Modules,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ConnectionStatus {
+    Connecting,
+    Connected,
+    /// Waiting to retry after the connection failed or was lost.
+    Disconnected,
+}
+
+/// Names of the devices selected when the connection was lost.
+#[derive(Default)]
+struct SavedSelection {
+    sink:   Option<String>,
+    source: Option<String>,
+    card:   Option<String>,
 }
 
 pub struct App {
@@ -335,39 +529,109 @@ pub struct App {
     sink_list:               SelectingMap<u32, SinkEntry>,
     source_list:             SelectingMap<u32, SourceEntry>,
     card_list:               SelectingMap<u32, CardEntry>,
+    module_list:             SelectingMap<u32, ModuleEntry>,
     sink_input_view_data:    views::sink_inputs::ViewData,
     source_output_view_data: views::source_outputs::ViewData,
     sink_view_data:          views::sinks::ViewData,
     source_view_data:        views::sources::ViewData,
     card_view_data:          views::cards::ViewData,
+    module_view_data:        views::modules::ViewData,
+    meters:                  meters::MeterData,
+    default_sink_name:       Option<String>,
+    default_source_name:     Option<String>,
     redraw:                  bool,
     view:                    AppView,
     hide_monitors:           bool,
+    group_streams:           bool,
+    max_volume:              u32,
+    click_targets:           Vec<(ratatui::layout::Rect, draw::ClickTarget)>,
+    config:                  config::Config,
+    connection_status:       ConnectionStatus,
+    saved_selection:         SavedSelection,
+    server:                  Option<String>,
+    server_label:            Option<String>,
+    server_popup:            Option<usize>,
+    server_switch_requested: bool,
+    status:                  status::StatusLine,
+    properties_popup:        Option<properties::PropertiesPopup>,
+    search:                  search::Search,
+    sorting:                 sorting::Sorting,
     quit_request:            bool,
 }
 
 impl App {
-    fn new() -> App {
+    /// The servers offered in the server popup: the default one, the ones from the config and the
+    /// current one.
+    fn server_choices(&self) -> Vec<Option<String>> {
+        let mut choices = vec![None];
+        choices.extend(self.config.servers.iter().cloned().map(Some));
+        if !choices.contains(&self.server) {
+            choices.push(self.server.clone());
+        }
+        return choices;
+    }
+
+    fn new(config: config::Config) -> App {
         App {
             sink_input_list:         SelectingMap::new(),
             source_output_list:      SelectingMap::new(),
             sink_list:               SelectingMap::new(),
             source_list:             SelectingMap::new(),
             card_list:               SelectingMap::new(),
+            module_list:             SelectingMap::new(),
             sink_input_view_data:    Default::default(),
             source_output_view_data: Default::default(),
             sink_view_data:          Default::default(),
             source_view_data:        Default::default(),
             card_view_data:          Default::default(),
+            module_view_data:        Default::default(),
+            meters:                  Default::default(),
+            default_sink_name:       None,
+            default_source_name:     None,
             redraw:                  true,
-            view:                    AppView::SinkInputs,
-            hide_monitors:           true,
+            view:                    config.start_view,
+            hide_monitors:           config.hide_monitors,
+            group_streams:           config.group_streams,
+            max_volume:              config.max_volume,
+            click_targets:           Vec::new(),
+            server:                  config.server.clone(),
+            config:                  config,
+            connection_status:       ConnectionStatus::Connecting,
+            saved_selection:         Default::default(),
+            server_label:            None,
+            server_popup:            None,
+            server_switch_requested: false,
+            status:                  Default::default(),
+            properties_popup:        None,
+            search:                  Default::default(),
+            sorting:                 Default::default(),
             quit_request:            false,
         }
     }
 }
 
 
+fn volume_from_percent(percent: u32) -> u32 {
+    let volume = pulse::volume::Volume::NORMAL.0 as u64 * percent as u64 / 100;
+    return volume.min(pulse::volume::Volume::MAX.0 as u64) as u32;
+}
+
+/// Parse a volume given as percentage, with or without trailing percent sign.
+fn parse_volume_percent(arg: &str) -> Option<u32> {
+    let percent = arg.strip_suffix('%').unwrap_or(arg).parse::<u32>().ok()?;
+    return Some(volume_from_percent(percent));
+}
+
+/// The ceiling for volume increases, `None` if the percentage is outside of
+/// `MAX_VOLUME_PERCENT_RANGE`.
+fn max_volume_from_percent(percent: u32) -> Option<u32> {
+    if !MAX_VOLUME_PERCENT_RANGE.contains(&percent) {
+        return None;
+    }
+    return Some(volume_from_percent(percent));
+}
+
+
 fn mainloop_iter(mainloop: &mut Mainloop, timeout: Option<pulse::time::MicroSeconds>) -> IterateResult
 {
     if let Result::Err(pae) = mainloop.prepare(timeout) {
@@ -391,301 +655,300 @@ fn mainloop_iter(mainloop: &mut Mainloop, timeout: Option<pulse::time::MicroSeco
 }
 
 
-fn main() {
-    let app = Arc::new(Mutex::new(App::new()));
-    // app.lock().unwrap().view = AppView::Cards;
-
-    // Connect to PA
+fn create_context(mainloop: &Mainloop) -> Context {
     let mut proplist = Proplist::new().expect("Proplist init failed");
     proplist.set_str(pulse::proplist::properties::APPLICATION_NAME, "Pavucontrolrs")
         .expect("Proplist setup failed");
+    proplist.set_str(pulse::proplist::properties::APPLICATION_ID, APPLICATION_ID)
+        .expect("Proplist setup failed");
 
-    let mainloop = Rc::new(RefCell::new(Mainloop::new()
-        .expect("Failed to create mainloop")));
-
-    let context = Arc::new(Mutex::new(Context::new_with_proplist(
-        mainloop.borrow().deref(),
+    return Context::new_with_proplist(
+        mainloop,
         "FooAppContext",
         &proplist
-        ).expect("Failed to create new context")));
+        ).expect("Failed to create new context");
+}
 
-    context.lock().unwrap().connect(None, pulse::context::FlagSet::NOFLAGS, None)
-        .expect("Failed to connect context");
 
-    // Wait for context to be ready
-    loop {
-        match mainloop.borrow_mut().iterate(false) {
-            IterateResult::Quit(_) |
-            IterateResult::Err(_) => {
-                eprintln!("iterate state was not success, quitting...");
-                return;
-            },
-            IterateResult::Success(_) => {},
-        }
-        match context.lock().unwrap().get_state() {
-            pulse::context::State::Ready => { break; },
-            pulse::context::State::Failed |
-            pulse::context::State::Terminated => {
-                eprintln!("context state failed/terminated, quitting...");
-                return;
-            },
-            _ => {},
+const RECONNECT_DELAY_MIN: std::time::Duration = std::time::Duration::from_millis(500);
+const RECONNECT_DELAY_MAX: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Keeps the context connected: once the connection fails or is lost, a new context is connected
+/// after a delay, which doubles with every failed attempt.
+struct Reconnect {
+    retry_at: std::time::Instant,
+    delay:    std::time::Duration,
+}
+
+impl Reconnect {
+    fn new() -> Reconnect {
+        Reconnect {
+            retry_at: std::time::Instant::now(),
+            delay:    RECONNECT_DELAY_MIN,
         }
     }
 
+    fn update(&mut self, app: &mut App, backend: &mut dyn backend::Backend) {
+        let state = backend.state();
 
-    // Retrieve initial sinkinput list
-    context.lock().unwrap().introspect().get_sink_input_info_list({
-        let app = Arc::clone(&app);
-        move |listres| {
-
-            match listres {
-                ListResult::Item(i) => {
-                    let mut app = app.lock().unwrap();
-                    app.sink_input_list.update(i.index, SinkInputEntry::from(i));
-                                    // println!("{}", i.proplist.to_string().unwrap());
-                },
-                ListResult::End |
-                ListResult::Error => {},
+        if std::mem::replace(&mut app.server_switch_requested, false) {
+            if app.connection_status == ConnectionStatus::Connected {
+                clear_server_state(app);
             }
-
+            backend.disconnect();
+            set_connection_status(app, ConnectionStatus::Disconnected);
+            self.retry_at = std::time::Instant::now();
+            self.delay = RECONNECT_DELAY_MIN;
+            return;
         }
-    });
 
-    // Retrieve initial sinkinput list
-    context.lock().unwrap().introspect().get_source_output_info_list({
-        let app = Arc::clone(&app);
-        move |listres| {
-
-            match listres {
-                ListResult::Item(i) => {
-                    let mut app = app.lock().unwrap();
-                    app.source_output_list.update(i.index, SourceOutputEntry::from(i));
-                },
-                ListResult::End |
-                ListResult::Error => {},
+        match (app.connection_status, state) {
+            (ConnectionStatus::Connecting, pulse::context::State::Ready) => {
+                set_connection_status(app, ConnectionStatus::Connected);
+                self.delay = RECONNECT_DELAY_MIN;
+                backend.subscribe();
+            }
+            (ConnectionStatus::Connecting, pulse::context::State::Failed) |
+            (ConnectionStatus::Connecting, pulse::context::State::Terminated) => {
+                self.retry_later(app);
             }
+            (ConnectionStatus::Connected, pulse::context::State::Failed) |
+            (ConnectionStatus::Connected, pulse::context::State::Terminated) => {
+                clear_server_state(app);
+                self.retry_later(app);
+            }
+            (ConnectionStatus::Disconnected, _) if std::time::Instant::now() >= self.retry_at => {
+                let server = app.server.clone();
+                if backend.connect(server.as_deref()) {
+                    set_connection_status(app, ConnectionStatus::Connecting);
+                } else {
+                    self.retry_later(app);
+                }
+            }
+            _ => {}
+        }
+    }
 
+    /// How long the mainloop may sleep before the next connection attempt is due.
+    fn timeout(&self, app: &App) -> Option<pulse::time::MicroSeconds> {
+        if app.connection_status != ConnectionStatus::Disconnected {
+            return None;
         }
-    });
+        let wait = self.retry_at.saturating_duration_since(std::time::Instant::now());
+        return Some(pulse::time::MicroSeconds(wait.as_micros() as u64));
+    }
+
+    fn retry_later(&mut self, app: &mut App) {
+        set_connection_status(app, ConnectionStatus::Disconnected);
+        self.retry_at = std::time::Instant::now() + self.delay;
+        self.delay = (self.delay * 2).min(RECONNECT_DELAY_MAX);
+    }
+}
 
-    // Retrieve initial sink list
-    context.lock().unwrap().introspect().get_sink_info_list({
-        let app = Arc::clone(&app);
-        move |listres| {
+fn set_connection_status(app: &mut App, status: ConnectionStatus) {
+    app.connection_status = status;
+    app.redraw = true;
+}
 
-            match listres {
-                ListResult::Item(i) => {
-                    let mut app = app.lock().unwrap();
-                    app.sink_list.update(i.index, SinkEntry::from(i));
-                                    // println!("{}", i.proplist.to_string().unwrap());
-                },
-                ListResult::End |
-                ListResult::Error => {},
-            }
+/// Keep the connection up, see `Reconnect`.
+fn update_connection(reconnect: &mut Reconnect, app_mutex: &Arc<Mutex<App>>, context_mutex: &Arc<Mutex<Context>>, mainloop: &Mainloop) {
 
-        }
-    });
+    let mut app = app_mutex.lock().unwrap();
+    let mut context = context_mutex.lock().unwrap();
 
-    // Retrieve initial source list
-    context.lock().unwrap().introspect().get_source_info_list({
-        let app = Arc::clone(&app);
-        move |listres| {
+    let mut backend = backend::PulseBackend {
+        context:  &mut context,
+        shared:   Arc::clone(context_mutex),
+        app:      Arc::clone(app_mutex),
+        mainloop: mainloop,
+    };
+    reconnect.update(&mut app, &mut backend);
+}
 
-            match listres {
-                ListResult::Item(i) => {
-                    let mut app = app.lock().unwrap();
-                    app.source_list.update(i.index, SourceEntry::from(i));
-                },
-                ListResult::End |
-                ListResult::Error => {},
-            }
+/// Forget everything known about the server after the connection was lost. The selected devices
+/// are remembered by name, to select them again once they show up after reconnecting.
+fn clear_server_state(app: &mut App) {
+    app.saved_selection = SavedSelection {
+        sink:   app.sink_list.get_selected().map(|x| x.name.clone()),
+        source: app.source_list.get_selected().map(|x| x.name.clone()),
+        card:   app.card_list.get_selected().map(|x| x.name.clone()),
+    };
 
-        }
-    });
-
-    // Retrieve initial card list
-    context.lock().unwrap().introspect().get_card_info_list({
-        let app = Arc::clone(&app);
-        move |listres| {
-
-            match listres {
-                ListResult::Item(i) => {
-                    let mut app = app.lock().unwrap();
-                    app.card_list.update(i.index, CardEntry::from(i));
-                    // let app = app.lock().unwrap();
-                    // println!("CARD: ");
-                    // println!("{}", i.proplist.to_string().unwrap());
-                    // for p in i.profiles.iter() {
-                    //     println!("{} {} {} {}", p.name.as_ref().unwrap(), p.priority, p.description.as_ref().unwrap(), p.available);
-                    // }
-                    // for port in i.ports.iter() {
-                    //     println!("{} {} {}", port.name.as_ref().unwrap(), port.priority, port.description.as_ref().unwrap());
-                    //     println!("{}", port.proplist.to_string().unwrap());
-                    // }
-                },
-                ListResult::End |
-                ListResult::Error => {},
-            }
+    app.sink_input_list.clear();
+    app.source_output_list.clear();
+    app.sink_list.clear();
+    app.source_list.clear();
+    app.card_list.clear();
+    app.module_list.clear();
+    app.default_sink_name = None;
+    app.default_source_name = None;
+    app.server_label = None;
+
+    // close popups, which refer to entries which are gone now
+    let view = app.view;
+    switch_view(app, view);
+}
 
+
+fn main() {
+    let mut config = match config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("failed to load config: {}", e);
+            return;
         }
-    });
-
-    // Subscribe to change events
-    let interest = InterestMaskSet::SINK_INPUT |
-        InterestMaskSet::SINK |
-        InterestMaskSet::SOURCE_OUTPUT |
-        InterestMaskSet::SOURCE |
-        InterestMaskSet::CARD;
-    context.lock().unwrap().subscribe(
-        interest,
-        |_| {}
-    );
-
-    context.lock().unwrap().set_subscribe_callback(Some(Box::new({
-        let app = Arc::clone(&app);
-        let context = Arc::clone(&context);
-        move |facility, operation, index| {
-
-            let app = Arc::clone(&app);
-            let facility = facility.expect("Subscribed event without Facility value");
-            let operation = operation.expect("Subscribed event without Operation value");
-            match facility {
-
-                Facility::SinkInput => {
-                    match operation {
-                        Operation::New | Operation::Changed => {
-                            context.lock().unwrap().introspect().get_sink_input_info(index,
-                                move |listres| {
-                                    if let ListResult::Item(i) = listres {
-                                        let mut app = app.lock().unwrap();
-                                        app.sink_input_list.update(i.index, SinkInputEntry::from(i));
-                                    }
-                                }
-                            );
-                        },
-                        Operation::Removed => {
-                            let mut app = app.lock().unwrap();
-                            app.sink_input_list.remove(index);
-                        }
+    };
+    naming::set_rules(std::mem::take(&mut config.naming));
+    let mut options = cli::Options {
+        max_volume: config.max_volume,
+        json:       false,
+        server:     config.server.clone(),
+    };
+    let mut command = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-volume" => {
+                let percent = args.next().and_then(|x| x.strip_suffix('%').unwrap_or(&x).parse::<u32>().ok());
+                match percent.and_then(max_volume_from_percent) {
+                    Some(volume) => { options.max_volume = volume; }
+                    None => {
+                        eprintln!("--max-volume expects a percentage from {}% to {}%, e.g. 150%",
+                            MAX_VOLUME_PERCENT_RANGE.start(), MAX_VOLUME_PERCENT_RANGE.end());
+                        std::process::exit(2);
                     }
                 }
-
-                Facility::SourceOutput => {
-                    match operation {
-                        Operation::New | Operation::Changed => {
-                            context.lock().unwrap().introspect().get_source_output_info(index,
-                                move |listres| {
-                                    if let ListResult::Item(i) = listres {
-                                        let mut app = app.lock().unwrap();
-                                        app.source_output_list.update(i.index, SourceOutputEntry::from(i));
-                                    }
-                                }
-                            );
-                        },
-                        Operation::Removed => {
-                            let mut app = app.lock().unwrap();
-                            app.source_output_list.remove(index);
-                        }
+            }
+            "--server" => {
+                match args.next() {
+                    Some(server) => { options.server = Some(server); }
+                    None => {
+                        eprintln!("--server expects a server address, e.g. tcp:localhost:4713");
+                        std::process::exit(2);
                     }
                 }
+            }
+            "--json" => {
+                options.json = true;
+            }
+            "-h" | "--help" => {
+                println!("{}", cli::USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("unknown argument: {}", arg);
+                eprintln!("{}", cli::USAGE);
+                std::process::exit(2);
+            }
+            _ => {
+                // everything from the first positional argument on belongs to the command, volume
+                // changes like -5% included
+                command.push(arg);
+                command.extend(args.by_ref());
+            }
+        }
+    }
 
-                Facility::Sink => {
-                    match operation {
-                        Operation::New | Operation::Changed => {
-                            context.lock().unwrap().introspect().get_sink_info_by_index(index,
-                                move |listres| {
-                                    if let ListResult::Item(i) = listres {
-                                        let mut app = app.lock().unwrap();
-                                        app.sink_list.update(i.index, SinkEntry::from(i));
-                                    }
-                                }
-                            );
-                        },
-                        Operation::Removed => {
-                            let mut app = app.lock().unwrap();
-                            app.sink_list.remove(index);
-                        }
-                    }
-                }
+    if !command.is_empty() || options.json {
+        std::process::exit(cli::run(&command, &options));
+    }
 
-                Facility::Source => {
-                    match operation {
-                        Operation::New | Operation::Changed => {
-                            context.lock().unwrap().introspect().get_source_info_by_index(index,
-                                move |listres| {
-                                    if let ListResult::Item(i) = listres {
-                                        let mut app = app.lock().unwrap();
-                                        app.source_list.update(i.index, SourceEntry::from(i));
-                                    }
-                                }
-                            );
-                        },
-                        Operation::Removed => {
-                            let mut app = app.lock().unwrap();
-                            app.source_list.remove(index);
-                        }
-                    }
-                }
+    let app = Arc::new(Mutex::new(App::new(config)));
+    app.lock().unwrap().max_volume = options.max_volume;
+    app.lock().unwrap().server = options.server.clone();
+    app.lock().unwrap().sorting = sorting::load();
+    // app.lock().unwrap().view = AppView::Cards;
 
-                Facility::Card => {
-                    match operation {
-                        Operation::New | Operation::Changed => {
-                            context.lock().unwrap().introspect().get_card_info_by_index(index,
-                                move |listres| {
-                                    if let ListResult::Item(i) = listres {
-                                        let mut app = app.lock().unwrap();
-                                        app.card_list.update(i.index, CardEntry::from(i));
-                                    }
-                                }
-                            );
-                        },
-                        Operation::Removed => {
-                            let mut app = app.lock().unwrap();
-                            app.card_list.remove(index);
-                        }
-                    }
-                }
+    // Connect to PA, the connection is established by Reconnect::update in the mainloop
+    let mainloop = Rc::new(RefCell::new(Mainloop::new()
+        .expect("Failed to create mainloop")));
 
-                _ => {}
-            }
+    let context = Arc::new(Mutex::new(create_context(mainloop.borrow().deref())));
 
-        }
-    })));
+    let mut reconnect = Reconnect::new();
+    if context.lock().unwrap().connect(options.server.as_deref(), pulse::context::FlagSet::NOFLAGS, None).is_err() {
+        reconnect.retry_later(&mut app.lock().unwrap());
+    }
 
 
     // // Terminal initialization
+    log::log_to_file();
+    let _crash_guard = crash::TerminalGuard::new();
     let mut terminal = match setup_terminal() {
         Ok(v)  => { v }
         Err(_) => { return; }
     };
 
-    // Start event/input handling thread
-    thread::spawn({
-        let app = Arc::clone(&app);
-        let context = Arc::clone(&context);
-        move || {
-            event_handler_thread(&app, &context);
-        }
-    });
+    // Terminal input is read on its own thread and wakes up the mainloop
+    let input = match input::Input::spawn(&mut mainloop.borrow_mut()) {
+        Ok(v)  => { v }
+        Err(_) => { return; }
+    };
+
+    // Level meter streams, created and destroyed as the displayed entries change
+    let mut meter_streams = meters::MeterStreams::new();
 
-    // Run PA mainloop
+    // Run PA mainloop, sleeping until the server, the terminal or a pending reconnect needs
+    // attention
     loop {
 
-        let timeout = pulse::time::MicroSeconds(10000); // in microseconds
+        update_connection(&mut reconnect, &app, &context, &mainloop.borrow());
 
-        match mainloop_iter(&mut mainloop.borrow_mut(), Some(timeout)) {
+        meter_streams.sync(&app, &context);
+
+        {
+            let mut app = app.lock().unwrap();
+            if app.status.expire() {
+                app.redraw = true;
+            }
+            draw_frame(&mut terminal, &mut app);
+        }
+
+        if app.lock().unwrap().quit_request {
+            return;
+        }
+
+        // wake up for the next connection attempt or to clear the status line
+        let status_timeout = app.lock().unwrap().status.expires_in().map(|x| pulse::time::MicroSeconds(x.as_micros() as u64));
+        let timeout = match (reconnect.timeout(&app.lock().unwrap()), status_timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b)             => a.or(b),
+        };
+
+        match mainloop_iter(&mut mainloop.borrow_mut(), timeout) {
             IterateResult::Quit(_) => {
                 return;
             }
             IterateResult::Err(_) => {
-                eprintln!("iterate state was not success, quitting...");
-                return;
+                // errors caused by losing the connection are handled by reconnecting
+                if context.lock().unwrap().get_state() == pulse::context::State::Ready {
+                    eprintln!("iterate state was not success, quitting...");
+                    return;
+                }
             },
             IterateResult::Success(_) => {},
         }
 
-        draw_frame(&mut terminal, &mut app.lock().unwrap());
+        // lists whose entries changed while dispatching are sorted again, and an entry matching
+        // the search stays selected
+        {
+            let mut app = app.lock().unwrap();
+            sorting::sort_all(&mut app);
+            search::keep_selections(&mut app);
+        }
+
+        while let Some(evt) = input.try_recv() {
+            match evt {
+                Event::Key(key) => {
+                    handle_key_event(key, &app, &context, &mainloop.borrow());
+                }
+                Event::Mouse(mouse_event) => {
+                    handle_mouse_event(mouse_event, &app, &context, &mainloop.borrow());
+                }
+                Event::Unsupported(_) => {}
+            }
+        }
 
         // {
         //     let mut app = app.lock().unwrap();
@@ -704,82 +967,326 @@ fn main() {
         //     }
         // }
 
-        if app.lock().unwrap().quit_request {
-            mainloop.borrow_mut().quit(Retval(0));
-        }
+    }
+}
+
 
+fn switch_view(app: &mut App, view: AppView) {
+    app.view = view;
+    app.properties_popup = None;
+    match view {
+        AppView::SinkInputs    => views::sink_inputs::entered(app),
+        AppView::SourceOutputs => views::source_outputs::entered(app),
+        AppView::Sinks         => views::sinks::entered(app),
+        AppView::Sources       => views::sources::entered(app),
+        AppView::Cards         => views::cards::entered(app),
+        AppView::Modules       => views::modules::entered(app),
     }
+    app.redraw = true;
 }
 
-fn event_handler_thread(app: &Mutex<App>, context: &Mutex<Context>) {
-    let stdin = io::stdin();
-    for evt in stdin.keys() {
-        match evt {
-            Ok(key) => {
-                handle_key_event(key, app, context);
-            }
-            Err(_) => {}
+fn handle_mouse_event(mouse_event: MouseEvent, app_mutex: &Arc<Mutex<App>>, context_mutex: &Arc<Mutex<Context>>, mainloop: &Mainloop) {
+
+    let mut app = app_mutex.lock().unwrap();
+    let mut context = context_mutex.lock().unwrap();
+
+    let (button, x, y) = match mouse_event {
+        MouseEvent::Press(button, x, y) => (button, x, y),
+        MouseEvent::Hold(x, y)          => (MouseButton::Left, x, y), // dragging along a gauge
+        MouseEvent::Release(_, _)       => { return; }
+    };
+
+    // termion coordinates are one-based
+    let position = ratatui::layout::Position { x: x.saturating_sub(1), y: y.saturating_sub(1) };
+
+    // popups are drawn last, so search from the back to hit the topmost target
+    let target = match app.click_targets.iter().rev().find(|(rect, _)| rect.contains(position)) {
+        Some((_, target)) => *target,
+        None => { return; }
+    };
+
+    if app.server_popup.is_some() {
+        if let (draw::ClickTarget::ServerEntry(i), MouseButton::Left) = (target, button) {
+            let server = app.server_choices()[i].clone();
+            select_server(&mut app, server);
         }
+        return;
+    }
+
+    if app.properties_popup.is_some() {
+        return;
+    }
+
+    if let draw::ClickTarget::Tab(view) = target {
+        if button == MouseButton::Left {
+            switch_view(&mut app, view);
+        }
+        return;
+    }
+
+    // requests on a context which is not connected panic
+    if context.get_state() != pulse::context::State::Ready {
+        return;
+    }
+
+    let mut backend = backend::PulseBackend {
+        context:  &mut context,
+        shared:   Arc::clone(context_mutex),
+        app:      Arc::clone(app_mutex),
+        mainloop: mainloop,
+    };
+
+    match app.view {
+        AppView::SinkInputs    => { views::sink_inputs::handle_mouse_event(target, button, position, &mut app, &mut backend); }
+        AppView::SourceOutputs => { views::source_outputs::handle_mouse_event(target, button, position, &mut app, &mut backend); }
+        AppView::Sinks         => { views::sinks::handle_mouse_event(target, button, position, &mut app, &mut backend); }
+        AppView::Sources       => { views::sources::handle_mouse_event(target, button, position, &mut app, &mut backend); }
+        AppView::Cards         => { views::cards::handle_mouse_event(target, button, position, &mut app, &mut backend); }
+        AppView::Modules       => { views::modules::handle_mouse_event(target, button, position, &mut app, &mut backend); }
     }
 }
 
-fn handle_key_event(key: Key, app: &Mutex<App>, context: &Mutex<Context>) {
+fn handle_key_event_server_popup(key: Key, app: &mut App) {
 
-    let mut app = app.lock().unwrap();
-    let context = context.lock().unwrap();
+    let section = config::KeymapSection::from(app.view);
+    let selected = match app.server_popup {
+        Some(selected) => selected,
+        None => return,
+    };
+    let choices = app.server_choices();
 
-    match key {
-        Key::Ctrl('c') |
-        Key::Char('q') => {
-            app.quit_request = true;
-            return;
+    match app.config.keymap.popup_action(section, key) {
+        Some(config::Action::ClosePopup) => {
+            app.server_popup = None;
         }
-        _ => {}
+        Some(config::Action::Confirm) => {
+            select_server(app, choices[selected].clone());
+        }
+        Some(config::Action::Down) => {
+            app.server_popup = Some((selected + 1).min(choices.len() - 1));
+        }
+        Some(config::Action::Up) => {
+            app.server_popup = Some(selected.saturating_sub(1));
+        }
+        _ => { return; }
     }
+    app.redraw = true;
+}
 
-    match key {
-        Key::F(1) => { app.view = AppView::SinkInputs;    views::sink_inputs::entered(&mut app);    app.redraw = true; return; }
-        Key::F(2) => { app.view = AppView::SourceOutputs; views::source_outputs::entered(&mut app); app.redraw = true; return; }
-        Key::F(3) => { app.view = AppView::Sinks;         views::sinks::entered(&mut app);          app.redraw = true; return; }
-        Key::F(4) => { app.view = AppView::Sources;       views::sources::entered(&mut app);        app.redraw = true; return; }
-        Key::F(5) => { app.view = AppView::Cards;         views::cards::entered(&mut app);          app.redraw = true; return; }
-        _ => {}
+/// Connect to another server, the actual reconnect happens in the mainloop.
+fn select_server(app: &mut App, server: Option<String>) {
+    app.server_popup = None;
+    if server != app.server || app.connection_status != ConnectionStatus::Connected {
+        app.server = server;
+        app.server_switch_requested = true;
+    }
+    app.redraw = true;
+}
+
+fn handle_key_event(key: Key, app_mutex: &Arc<Mutex<App>>, context_mutex: &Arc<Mutex<Context>>, mainloop: &Mainloop) {
+
+    let mut app = app_mutex.lock().unwrap();
+    let mut context = context_mutex.lock().unwrap();
+
+    if app.server_popup.is_some() {
+        handle_key_event_server_popup(key, &mut app);
+        return;
     }
 
-    if key == Key::Char('\t') {
-        match app.view {
-            AppView::SinkInputs    => { app.view = AppView::SourceOutputs; views::source_outputs::entered(&mut app); }
-            AppView::SourceOutputs => { app.view = AppView::Sinks;         views::sinks::entered(&mut app);          }
-            AppView::Sinks         => { app.view = AppView::Sources;       views::sources::entered(&mut app);        }
-            AppView::Sources       => { app.view = AppView::Cards;         views::cards::entered(&mut app);          }
-            AppView::Cards         => { app.view = AppView::SinkInputs;    views::sink_inputs::entered(&mut app);    }
-        }
-        app.redraw = true;
+    if app.properties_popup.is_some() {
+        properties::handle_key_event(key, &mut app);
         return;
     }
 
-    if key == Key::Char('M') {
-        app.hide_monitors = !app.hide_monitors;
-        if app.hide_monitors {
-            app.source_list.filtered_select_next_else_prev(|x| !x.is_monitor());
+    if app.search.editing {
+        search::handle_key_event(key, &mut app);
+        return;
+    }
 
-            {
-                let app = &mut *app; // XXX
-                let s_list = &app.source_list;
-                app.source_output_list.filtered_select_next_else_prev(|x|
-                    !(x.source_index == 0xffffffff || s_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false))
-                );
+    // the name of a module to load is typed, keys are text rather than actions
+    if app.module_view_data.is_typing() {
+        if context.get_state() != pulse::context::State::Ready {
+            // the connection was lost while typing, there is nothing to load the module into
+            app.module_view_data.close_load_prompt();
+            app.redraw = true;
+            return;
+        }
+        let mut backend = backend::PulseBackend {
+            context:  &mut context,
+            shared:   Arc::clone(context_mutex),
+            app:      Arc::clone(app_mutex),
+            mainloop: mainloop,
+        };
+        views::modules::handle_key_event(key, &mut app, &mut backend);
+        return;
+    }
+
+    match app.config.keymap.action(config::KeymapSection::Global, key) {
+        Some(config::Action::Quit) => {
+            app.quit_request = true;
+            return;
+        }
+        Some(config::Action::ShowView(view)) => {
+            switch_view(&mut app, view);
+            return;
+        }
+        Some(config::Action::NextView) => {
+            let next_view = match app.view {
+                AppView::SinkInputs    => AppView::SourceOutputs,
+                AppView::SourceOutputs => AppView::Sinks,
+                AppView::Sinks         => AppView::Sources,
+                AppView::Sources       => AppView::Cards,
+                AppView::Cards         => AppView::Modules,
+                AppView::Modules       => AppView::SinkInputs,
+            };
+            switch_view(&mut app, next_view);
+            return;
+        }
+        Some(config::Action::SwitchServer) => {
+            let current = app.server.clone();
+            app.server_popup = app.server_choices().iter().position(|x| *x == current);
+            app.redraw = true;
+            return;
+        }
+        Some(config::Action::Properties) => {
+            if properties::rows(&app).is_some() {
+                app.properties_popup = Some(Default::default());
+                app.redraw = true;
+            }
+            return;
+        }
+        Some(config::Action::Search) => {
+            let view = app.view;
+            app.search.start(view);
+            app.redraw = true;
+            return;
+        }
+        Some(config::Action::NextMatch) => {
+            search::jump(&mut app, true);
+            return;
+        }
+        Some(config::Action::PrevMatch) => {
+            search::jump(&mut app, false);
+            return;
+        }
+        Some(config::Action::CycleSort) => {
+            let view = app.view;
+            let mode = app.sorting.cycle(view);
+            sorting::sort_all(&mut app);
+            match sorting::save(&app.sorting) {
+                Ok(()) => app.status.info(sorting::describe(mode)),
+                Err(e) => app.status.error(format!("{}, saving it failed: {}", sorting::describe(mode), e)),
+            }
+            app.redraw = true;
+            return;
+        }
+        Some(config::Action::ToggleMeters) => {
+            app.meters.toggle();
+            app.redraw = true;
+            return;
+        }
+        Some(config::Action::ToggleMonitors) => {
+            app.hide_monitors = !app.hide_monitors;
+            if app.hide_monitors {
+                app.source_list.filtered_select_next_else_prev(|x| !x.is_monitor());
+
+                {
+                    let app = &mut *app; // XXX
+                    let s_list = &app.source_list;
+                    app.source_output_list.filtered_select_next_else_prev(|x|
+                        !(x.source_index == 0xffffffff || s_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false))
+                    );
+                }
             }
+            search::keep_selections(&mut app);
+            app.redraw = true;
+            return;
         }
-        app.redraw = true;
+        _ => {}
+    }
+
+    // requests on a context which is not connected panic
+    if context.get_state() != pulse::context::State::Ready {
         return;
     }
 
+    let mut backend = backend::PulseBackend {
+        context:  &mut context,
+        shared:   Arc::clone(context_mutex),
+        app:      Arc::clone(app_mutex),
+        mainloop: mainloop,
+    };
+
     match app.view {
-        AppView::SinkInputs    => { views::sink_inputs::handle_key_event(key, &mut app, &context); }
-        AppView::SourceOutputs => { views::source_outputs::handle_key_event(key, &mut app, &context); }
-        AppView::Sinks         => { views::sinks::handle_key_event(key, &mut app, &context); }
-        AppView::Sources       => { views::sources::handle_key_event(key, &mut app, &context); }
-        AppView::Cards         => { views::cards::handle_key_event(key, &mut app, &context); }
+        AppView::SinkInputs    => { views::sink_inputs::handle_key_event(key, &mut app, &mut backend); }
+        AppView::SourceOutputs => { views::source_outputs::handle_key_event(key, &mut app, &mut backend); }
+        AppView::Sinks         => { views::sinks::handle_key_event(key, &mut app, &mut backend); }
+        AppView::Sources       => { views::sources::handle_key_event(key, &mut app, &mut backend); }
+        AppView::Cards         => { views::cards::handle_key_event(key, &mut app, &mut backend); }
+        AppView::Modules       => { views::modules::handle_key_event(key, &mut app, &mut backend); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Call, FakeBackend};
+    use crate::test_util::test_sink;
+
+    #[test]
+    fn reconnect_subscribes_once_ready() {
+        let mut app = App::new(Default::default());
+        let mut backend = FakeBackend::default();
+        let mut reconnect = Reconnect::new();
+        reconnect.retry_later(&mut app);
+        reconnect.retry_at = std::time::Instant::now();
+
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(app.connection_status, ConnectionStatus::Connecting);
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(app.connection_status, ConnectionStatus::Connecting);
+
+        backend.state = pulse::context::State::Ready;
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(app.connection_status, ConnectionStatus::Connected);
+        assert_eq!(backend.calls, vec![Call::Connect(None), Call::Subscribe]);
+    }
+
+    #[test]
+    fn lost_connection_clears_entries_and_waits() {
+        let mut app = App::new(Default::default());
+        app.connection_status = ConnectionStatus::Connected;
+        app.sink_list.update(1, test_sink(1, "speakers"));
+        app.sink_list.update(2, test_sink(2, "headset"));
+        app.sink_list.select(2);
+        let mut backend = FakeBackend::default();
+        let mut reconnect = Reconnect::new();
+
+        backend.state = pulse::context::State::Failed;
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(app.connection_status, ConnectionStatus::Disconnected);
+        assert_eq!(app.sink_list.len(), 0);
+        assert_eq!(app.saved_selection.sink.as_deref(), Some("headset"));
+
+        // the next attempt waits for the delay
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(backend.calls, vec![]);
+        assert!(reconnect.timeout(&app).is_some());
+    }
+
+    #[test]
+    fn server_switch_disconnects() {
+        let mut app = App::new(Default::default());
+        app.connection_status = ConnectionStatus::Connected;
+        app.sink_list.update(1, test_sink(1, "speakers"));
+        let mut backend = FakeBackend::default();
+        let mut reconnect = Reconnect::new();
+
+        select_server(&mut app, Some(String::from("tcp:mediapc:4713")));
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(app.sink_list.len(), 0);
+        reconnect.update(&mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::Disconnect, Call::Connect(Some(String::from("tcp:mediapc:4713")))]);
+        assert_eq!(app.connection_status, ConnectionStatus::Connecting);
     }
 }
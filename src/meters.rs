@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use pulse::context::Context;
+use pulse::stream::{Stream, PeekResult};
+
+use crate::{App, AppView};
+
+/// Sample rate of the peak detecting record streams, i.e. the number of meter updates per second.
+pub const METER_RATE: u32 = 25;
+
+/// What a peak detecting stream is recording from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MeterTarget {
+    Source(u32),
+    SinkInput { index: u32, monitor_source: u32 },
+}
+
+/// Peak levels as last reported by the record streams, shared with the drawing code.
+pub struct MeterData {
+    enabled:          bool,
+    source_peaks:     HashMap<u32, f32>,
+    sink_input_peaks: HashMap<u32, f32>,
+    changed:          bool,
+}
+
+impl Default for MeterData {
+    fn default() -> MeterData {
+        MeterData {
+            enabled:          true,
+            source_peaks:     HashMap::new(),
+            sink_input_peaks: HashMap::new(),
+            changed:          false,
+        }
+    }
+}
+
+impl MeterData {
+    pub fn is_enabled(&self) -> bool {
+        return self.enabled;
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.changed = true;
+    }
+
+    pub fn source_peak(&self, index: u32) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+        return self.source_peaks.get(&index).copied();
+    }
+
+    pub fn sink_input_peak(&self, index: u32) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+        return self.sink_input_peaks.get(&index).copied();
+    }
+
+    pub fn reset_changed(&mut self) -> bool {
+        let t = self.changed;
+        self.changed = false;
+        return t;
+    }
+
+    #[cfg(test)]
+    pub fn set_source_peak(&mut self, index: u32, peak: f32) {
+        self.update(MeterTarget::Source(index), peak);
+    }
+
+    fn update(&mut self, target: MeterTarget, peak: f32) {
+        match target {
+            MeterTarget::Source(index)            => { self.source_peaks.insert(index, peak); }
+            MeterTarget::SinkInput { index, .. } => { self.sink_input_peaks.insert(index, peak); }
+        }
+        self.changed = true;
+    }
+
+    fn clear(&mut self, target: MeterTarget) {
+        match target {
+            MeterTarget::Source(index)            => { self.source_peaks.remove(&index); }
+            MeterTarget::SinkInput { index, .. } => { self.sink_input_peaks.remove(&index); }
+        }
+        self.changed = true;
+    }
+}
+
+/// Owns the peak detecting record streams. Lives on the mainloop thread, as streams are not Send.
+pub struct MeterStreams {
+    streams: HashMap<MeterTarget, Rc<RefCell<Stream>>>,
+}
+
+impl MeterStreams {
+    pub fn new() -> MeterStreams {
+        MeterStreams {
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Create and destroy record streams so that exactly the entries shown in the current view are
+    /// metered. Only metering what is visible keeps the number of streams, and thereby the load on
+    /// the server, low when there are many entries.
+    pub fn sync(&mut self, app: &Arc<Mutex<App>>, context: &Mutex<Context>) {
+        let mut app_locked = app.lock().unwrap();
+
+        let wanted = if app_locked.meters.enabled {
+            wanted_targets(&app_locked)
+        } else {
+            HashSet::new()
+        };
+
+        let meters = &mut app_locked.meters;
+        self.streams.retain(|target, stream| {
+            if wanted.contains(target) {
+                return true;
+            }
+            let _ = stream.borrow_mut().disconnect();
+            meters.clear(*target);
+            return false;
+        });
+
+        let missing: Vec<MeterTarget> = wanted.into_iter().filter(|t| !self.streams.contains_key(t)).collect();
+        if missing.is_empty() {
+            return;
+        }
+        drop(app_locked);
+
+        let mut context = context.lock().unwrap();
+        for target in missing {
+            // streams failing later on stay in the map, so they are not recreated on every iteration
+            if let Some(stream) = create_stream(target, app, &mut context) {
+                self.streams.insert(target, stream);
+            }
+        }
+    }
+}
+
+fn wanted_targets(app: &App) -> HashSet<MeterTarget> {
+    match app.view {
+        AppView::SinkInputs => {
+            app.sink_input_list.values()
+                .filter_map(|x| app.sink_list.get(x.sink_index).map(|s| MeterTarget::SinkInput { index: x.index, monitor_source: s.monitor_source }))
+                .collect()
+        }
+        AppView::SourceOutputs => {
+            app.source_output_list.values()
+                .filter(|x| app.source_list.get(x.source_index).is_some())
+                .map(|x| MeterTarget::Source(x.source_index))
+                .collect()
+        }
+        AppView::Sinks => {
+            app.sink_list.values().map(|x| MeterTarget::Source(x.monitor_source)).collect()
+        }
+        AppView::Sources => {
+            app.source_list.filtered_values(|x| !(x.is_monitor() && app.hide_monitors))
+                .map(|x| MeterTarget::Source(x.index))
+                .collect()
+        }
+        AppView::Cards | AppView::Modules => HashSet::new(),
+    }
+}
+
+fn create_stream(target: MeterTarget, app: &Arc<Mutex<App>>, context: &mut Context) -> Option<Rc<RefCell<Stream>>> {
+    let spec = pulse::sample::Spec {
+        format:   pulse::sample::Format::FLOAT32NE,
+        channels: 1,
+        rate:     METER_RATE,
+    };
+    let stream = Rc::new(RefCell::new(Stream::new(context, "Peak detect", &spec, None)?));
+
+    let source_index = match target {
+        MeterTarget::Source(index) => index,
+        MeterTarget::SinkInput { index, monitor_source } => {
+            stream.borrow_mut().set_monitor_stream(index).ok()?;
+            monitor_source
+        }
+    };
+
+    stream.borrow_mut().set_read_callback(Some(Box::new({
+        let weak_stream = Rc::downgrade(&stream);
+        let app = Arc::clone(app);
+        move |_| {
+            let stream = match weak_stream.upgrade() {
+                Some(stream) => stream,
+                None => return,
+            };
+            let mut stream = stream.borrow_mut();
+
+            let mut peak = None;
+            loop {
+                match stream.peek() {
+                    Ok(PeekResult::Empty) | Err(_) => break,
+                    Ok(PeekResult::Hole(_)) => {}
+                    Ok(PeekResult::Data(data)) => {
+                        if let Some(sample) = data.rchunks_exact(4).next() {
+                            peak = Some(f32::from_ne_bytes([sample[0], sample[1], sample[2], sample[3]]));
+                        }
+                    }
+                }
+                if stream.discard().is_err() {
+                    break;
+                }
+            }
+
+            if let Some(peak) = peak {
+                app.lock().unwrap().meters.update(target, peak.clamp(0.0, 1.0));
+            }
+        }
+    })));
+
+    let attr = pulse::def::BufferAttr {
+        maxlength: u32::MAX,
+        tlength:   u32::MAX,
+        prebuf:    u32::MAX,
+        minreq:    u32::MAX,
+        fragsize:  std::mem::size_of::<f32>() as u32,
+    };
+    let flags = pulse::stream::FlagSet::DONT_MOVE
+        | pulse::stream::FlagSet::PEAK_DETECT
+        | pulse::stream::FlagSet::ADJUST_LATENCY
+        | pulse::stream::FlagSet::DONT_INHIBIT_AUTO_SUSPEND;
+    stream.borrow_mut().connect_record(Some(&source_index.to_string()), Some(&attr), flags).ok()?;
+
+    return Some(stream);
+}
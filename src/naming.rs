@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use pulse::proplist::Proplist;
+use regex::Regex;
+
+static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+
+/// A rule for the name shown for a stream: if all properties match their patterns, the template
+/// gives the name.
+pub struct Rule {
+    matches:  Vec<(String, Regex)>,
+    template: String,
+}
+
+impl Rule {
+    /// Patterns are globs like `*chrom*`, or regular expressions if enclosed in slashes like
+    /// `/^Wine.*/`. The template contains property keys in braces like `{media.name}`, `{name}`
+    /// being the name of the stream.
+    pub fn new(matches: &HashMap<String, String>, template: &str) -> Result<Rule, String> {
+        let mut compiled = Vec::new();
+        for (key, pattern) in matches.iter() {
+            let regex = parse_pattern(pattern).map_err(|e| format!("invalid pattern '{}' for '{}': {}", pattern, key, e))?;
+            compiled.push((key.clone(), regex));
+        }
+        parse_template(template)?;
+        return Ok(Rule { matches: compiled, template: String::from(template) });
+    }
+
+    /// The name for a stream, or `None` if the rule does not apply. A rule also does not apply if
+    /// its template uses a property the stream does not have.
+    fn apply(&self, name: &str, proplist: &Proplist) -> Option<String> {
+        for (key, regex) in self.matches.iter() {
+            if !regex.is_match(&proplist.get_str(key)?) {
+                return None;
+            }
+        }
+
+        let mut result = String::new();
+        for part in parse_template(&self.template).expect("Template was checked when creating the rule") {
+            match part {
+                TemplatePart::Text(text)  => result.push_str(text),
+                TemplatePart::Key("name") => result.push_str(name),
+                TemplatePart::Key(key)    => result.push_str(&proplist.get_str(key)?),
+            }
+        }
+        return Some(result);
+    }
+}
+
+enum TemplatePart<'a> {
+    Text(&'a str),
+    Key(&'a str),
+}
+
+fn parse_template(template: &str) -> Result<Vec<TemplatePart<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| format!("unclosed '{{' in '{}'", template))?;
+        parts.push(TemplatePart::Text(&rest[..start]));
+        parts.push(TemplatePart::Key(&rest[start + 1..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    parts.push(TemplatePart::Text(rest));
+    return Ok(parts);
+}
+
+fn parse_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = pattern.strip_prefix('/').and_then(|x| x.strip_suffix('/')) {
+        return Regex::new(regex);
+    }
+
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _   => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    return Regex::new(&regex);
+}
+
+/// The stream name followed by the application, as shown without any configured rules. Firefox
+/// streams keep the `[firefox]` label they always had.
+fn default_rules() -> Vec<Rule> {
+    let firefox = [(String::from("application.process.binary"), String::from("firefox"))].iter().cloned().collect();
+    let application = [(String::from("application.name"), String::from("*"))].iter().cloned().collect();
+    return vec![
+        Rule::new(&firefox, "{name} [firefox]").expect("Default naming rule is invalid"),
+        Rule::new(&application, "{name} [{application.name}]").expect("Default naming rule is invalid"),
+    ];
+}
+
+/// Use the rules from the config, falling back to the default ones. Only the first call has an
+/// effect.
+pub fn set_rules(mut rules: Vec<Rule>) {
+    rules.extend(default_rules());
+    let _ = RULES.set(rules);
+}
+
+/// The name shown for a stream, given by the first rule that applies.
+pub fn display_name(name: &str, proplist: &Proplist) -> String {
+    let rules = RULES.get_or_init(default_rules);
+    return rules.iter().find_map(|x| x.apply(name, proplist)).unwrap_or_else(|| String::from(name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proplist(props: &[(&str, &str)]) -> Proplist {
+        let mut proplist = Proplist::new().unwrap();
+        for (key, value) in props.iter() {
+            proplist.set_str(key, value).unwrap();
+        }
+        return proplist;
+    }
+
+    fn rule(matches: &[(&str, &str)], template: &str) -> Rule {
+        let matches = matches.iter().map(|(k, v)| (String::from(*k), String::from(*v))).collect();
+        return Rule::new(&matches, template).unwrap();
+    }
+
+    #[test]
+    fn glob_pattern() {
+        let rule = rule(&[("application.process.binary", "*chrom*")], "{media.name} [Chromium]");
+        let props = proplist(&[("application.process.binary", "chromium-browser"), ("media.name", "Playback")]);
+        assert_eq!(rule.apply("stream", &props), Some(String::from("Playback [Chromium]")));
+        let props = proplist(&[("application.process.binary", "firefox"), ("media.name", "Playback")]);
+        assert_eq!(rule.apply("stream", &props), None);
+    }
+
+    #[test]
+    fn regex_pattern() {
+        let rule = rule(&[("application.name", "/^wine/")], "{name} (Wine)");
+        assert_eq!(rule.apply("game", &proplist(&[("application.name", "wine64-preloader")])), Some(String::from("game (Wine)")));
+        assert_eq!(rule.apply("game", &proplist(&[("application.name", "steam")])), None);
+    }
+
+    #[test]
+    fn missing_template_key() {
+        let rule = rule(&[], "{window.title}");
+        assert_eq!(rule.apply("stream", &proplist(&[])), None);
+    }
+
+    #[test]
+    fn default_names() {
+        let rules = default_rules();
+        let name = |props: &[(&str, &str)]| rules.iter().find_map(|x| x.apply("Playback", &proplist(props)));
+        assert_eq!(name(&[("application.process.binary", "firefox"), ("application.name", "Firefox")]), Some(String::from("Playback [firefox]")));
+        assert_eq!(name(&[("application.process.binary", "mpv"), ("application.name", "mpv Media Player")]), Some(String::from("Playback [mpv Media Player]")));
+        assert_eq!(name(&[]), None);
+    }
+
+    #[test]
+    fn invalid_template() {
+        assert!(Rule::new(&HashMap::new(), "{media.name").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use termion::event::Key;
+
+use pulse::proplist::Proplist;
+
+use crate::config::{Action, KeymapSection};
+use crate::{App, AppView, CardEntry, ModuleEntry, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+
+/// State of the popup listing all properties of the selected entry.
+#[derive(Default)]
+pub struct PropertiesPopup {
+    pub scroll:  usize,
+    /// Only rows whose key or value contain this are shown, ignoring case.
+    pub filter:  String,
+    /// Whether keys are typed into the filter.
+    pub editing: bool,
+}
+
+impl PropertiesPopup {
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        let filter = self.filter.to_lowercase();
+        return key.to_lowercase().contains(&filter) || value.to_lowercase().contains(&filter);
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    return value.map(|x| x.to_string()).unwrap_or_else(|| String::from("-"));
+}
+
+fn latency(latency: pulse::time::MicroSeconds) -> String {
+    return format!("{:.1} ms", latency.0 as f64 / 1000f64);
+}
+
+fn proplist_rows(proplist: &Proplist, rows: &mut Vec<(String, String)>) {
+    let mut keys: Vec<String> = proplist.iter().collect();
+    keys.sort();
+    for key in keys {
+        if let Some(value) = proplist.get_str(&key) {
+            rows.push((key, value));
+        }
+    }
+}
+
+fn sink_rows(entry: &SinkEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),        entry.index.to_string()),
+        (String::from("Name"),         entry.name.clone()),
+        (String::from("Description"),  entry.description.clone()),
+        (String::from("Driver"),       optional(entry.driver.as_ref())),
+        (String::from("Owner module"), optional(entry.owner_module)),
+        (String::from("Card"),         optional(entry.card)),
+        (String::from("State"),        String::from(crate::json::sink_state(entry.state))),
+        (String::from("Sample spec"),  entry.sample_spec.print()),
+        (String::from("Channel map"),  entry.channel_map.print()),
+        (String::from("Latency"),      latency(entry.latency)),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+fn source_rows(entry: &SourceEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),           entry.index.to_string()),
+        (String::from("Name"),            entry.name.clone()),
+        (String::from("Description"),     entry.description.clone()),
+        (String::from("Driver"),          optional(entry.driver.as_ref())),
+        (String::from("Owner module"),    optional(entry.owner_module)),
+        (String::from("Card"),            optional(entry.card)),
+        (String::from("Monitor of sink"), optional(entry.monitor_of_sink)),
+        (String::from("State"),           String::from(crate::json::source_state(entry.state))),
+        (String::from("Sample spec"),     entry.sample_spec.print()),
+        (String::from("Channel map"),     entry.channel_map.print()),
+        (String::from("Latency"),         latency(entry.latency)),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+fn sink_input_rows(entry: &SinkInputEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),          entry.index.to_string()),
+        (String::from("Name"),           entry.name.clone()),
+        (String::from("Driver"),         optional(entry.driver.as_ref())),
+        (String::from("Owner module"),   optional(entry.owner_module)),
+        (String::from("Client"),         optional(entry.client)),
+        (String::from("Sink"),           entry.sink_index.to_string()),
+        (String::from("Corked"),         entry.corked.to_string()),
+        (String::from("Sample spec"),    entry.sample_spec.print()),
+        (String::from("Channel map"),    entry.channel_map.print()),
+        (String::from("Buffer latency"), latency(entry.buffer_latency)),
+        (String::from("Sink latency"),   latency(entry.latency)),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+fn source_output_rows(entry: &SourceOutputEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),          entry.index.to_string()),
+        (String::from("Name"),           entry.name.clone()),
+        (String::from("Driver"),         optional(entry.driver.as_ref())),
+        (String::from("Owner module"),   optional(entry.owner_module)),
+        (String::from("Client"),         optional(entry.client)),
+        (String::from("Source"),         entry.source_index.to_string()),
+        (String::from("Corked"),         entry.corked.to_string()),
+        (String::from("Sample spec"),    entry.sample_spec.print()),
+        (String::from("Channel map"),    entry.channel_map.print()),
+        (String::from("Buffer latency"), latency(entry.buffer_latency)),
+        (String::from("Source latency"), latency(entry.latency)),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+fn card_rows(entry: &CardEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),          entry.index.to_string()),
+        (String::from("Name"),           entry.name.clone()),
+        (String::from("Driver"),         optional(entry.driver.as_ref())),
+        (String::from("Owner module"),   optional(entry.owner_module)),
+        (String::from("Active profile"), optional(entry.active_profile_index.map(|i| &entry.profiles[i].name))),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+fn module_rows(entry: &ModuleEntry) -> Vec<(String, String)> {
+    let mut rows = vec![
+        (String::from("Index"),    entry.index.to_string()),
+        (String::from("Name"),     entry.name.clone()),
+        (String::from("Argument"), optional(entry.argument.as_ref())),
+        (String::from("Used"),     optional(entry.n_used)),
+    ];
+    proplist_rows(&entry.proplist, &mut rows);
+    return rows;
+}
+
+/// The title and rows of the popup, for the entry selected in the current view.
+pub fn rows(app: &App) -> Option<(String, Vec<(String, String)>)> {
+    match app.view {
+        AppView::SinkInputs    => app.sink_input_list.get_selected().map(|x| (x.display_name(), sink_input_rows(x))),
+        AppView::SourceOutputs => app.source_output_list.get_selected().map(|x| (x.display_name(), source_output_rows(x))),
+        AppView::Sinks         => app.sink_list.get_selected().map(|x| (x.display_name(), sink_rows(x))),
+        AppView::Sources       => app.source_list.get_selected().map(|x| (x.display_name(), source_rows(x))),
+        AppView::Cards         => app.card_list.get_selected().map(|x| (x.display_name(), card_rows(x))),
+        AppView::Modules       => app.module_list.get_selected().map(|x| (x.display_name(), module_rows(x))),
+    }
+}
+
+/// Keys while the popup is open: the search key starts typing a filter, which return or escape end. Otherwise
+/// the popup scrolls and closes like the other popups, closing clears a filter first.
+pub fn handle_key_event(key: Key, app: &mut App) {
+
+    let popup = match app.properties_popup.as_mut() {
+        Some(popup) => popup,
+        None => return,
+    };
+
+    if popup.editing {
+        match key {
+            Key::Char('\n') | Key::Esc => { popup.editing = false; }
+            Key::Backspace             => { popup.filter.pop(); }
+            Key::Char(c)               => { popup.filter.push(c); popup.scroll = 0; }
+            _ => { return; }
+        }
+        app.redraw = true;
+        return;
+    }
+
+    if app.config.keymap.action(KeymapSection::Global, key) == Some(Action::Search) {
+        popup.editing = true;
+        popup.filter.clear();
+        popup.scroll = 0;
+        app.redraw = true;
+        return;
+    }
+
+    match app.config.keymap.popup_action(KeymapSection::from(app.view), key) {
+        Some(Action::ClosePopup) => {
+            if popup.filter.is_empty() {
+                app.properties_popup = None;
+            } else {
+                popup.filter.clear();
+            }
+        }
+        Some(Action::Down) => {
+            popup.scroll += 1;
+        }
+        Some(Action::Up) => {
+            popup.scroll = popup.scroll.saturating_sub(1);
+        }
+        _ => { return; }
+    }
+    app.redraw = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_sink;
+
+    #[test]
+    fn filter_starts_with_the_search_key() {
+        let config = crate::config::parse("[keys.global]\nsearch = \"f\"\n").unwrap();
+        let mut app = App::new(config);
+        app.sink_list.update(1, test_sink(1, "speakers"));
+        app.view = AppView::Sinks;
+        app.properties_popup = Some(Default::default());
+
+        handle_key_event(Key::Char('/'), &mut app);
+        assert!(!app.properties_popup.as_ref().unwrap().editing);
+        handle_key_event(Key::Char('f'), &mut app);
+        handle_key_event(Key::Char('x'), &mut app);
+        let popup = app.properties_popup.as_ref().unwrap();
+        assert!(popup.editing);
+        assert_eq!(popup.filter, "x");
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use termion::event::Key;
+
+use crate::selecting_map::SelectingMap;
+use crate::{App, AppView, CardEntry, ModuleEntry, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+
+/// Entries that can be found with `/`.
+pub trait Searchable {
+    /// The texts a search is matched against.
+    fn search_texts(&self) -> Vec<String>;
+}
+
+impl Searchable for SinkInputEntry {
+    fn search_texts(&self) -> Vec<String> {
+        let mut texts = vec![self.display_name()];
+        texts.extend(self.proplist.get_str("application.name"));
+        texts.extend(self.proplist.get_str("application.process.binary"));
+        return texts;
+    }
+}
+
+impl Searchable for SourceOutputEntry {
+    fn search_texts(&self) -> Vec<String> {
+        let mut texts = vec![self.display_name()];
+        texts.extend(self.proplist.get_str("application.name"));
+        texts.extend(self.proplist.get_str("application.process.binary"));
+        return texts;
+    }
+}
+
+impl Searchable for SinkEntry {
+    fn search_texts(&self) -> Vec<String> {
+        let mut texts = vec![self.display_name(), self.name.clone()];
+        texts.extend(self.proplist.get_str("device.description"));
+        return texts;
+    }
+}
+
+impl Searchable for SourceEntry {
+    fn search_texts(&self) -> Vec<String> {
+        let mut texts = vec![self.display_name(), self.name.clone()];
+        texts.extend(self.proplist.get_str("device.description"));
+        return texts;
+    }
+}
+
+impl Searchable for CardEntry {
+    fn search_texts(&self) -> Vec<String> {
+        return vec![self.display_name(), self.name.clone()];
+    }
+}
+
+impl Searchable for ModuleEntry {
+    fn search_texts(&self) -> Vec<String> {
+        let mut texts = vec![self.display_name()];
+        texts.extend(self.argument.clone());
+        return texts;
+    }
+}
+
+/// Whether an entry matches a lowercase search, an empty search matches everything.
+pub fn matches<T: Searchable>(entry: &T, search: &str) -> bool {
+    return search.is_empty() || entry.search_texts().iter().any(|x| x.to_lowercase().contains(search));
+}
+
+/// The search of each view. While typing, the search of the current view is edited.
+#[derive(Default)]
+pub struct Search {
+    pub editing: bool,
+    searches:    HashMap<AppView, String>,
+}
+
+impl Search {
+    /// The search of a view, lowercased for `matches`.
+    pub fn get(&self, view: AppView) -> String {
+        return self.searches.get(&view).map(|x| x.to_lowercase()).unwrap_or_default();
+    }
+
+    /// The search as typed, for display.
+    pub fn text(&self, view: AppView) -> &str {
+        return self.searches.get(&view).map(|x| x.as_str()).unwrap_or("");
+    }
+
+    pub fn start(&mut self, view: AppView) {
+        self.editing = true;
+        self.searches.remove(&view);
+    }
+}
+
+/// Keys while typing a search: return keeps it, escape drops it.
+pub fn handle_key_event(key: Key, app: &mut App) {
+    let view = app.view;
+    match key {
+        Key::Char('\n') => {
+            app.search.editing = false;
+        }
+        Key::Esc => {
+            app.search.editing = false;
+            app.search.searches.remove(&view);
+        }
+        Key::Backspace => {
+            app.search.searches.entry(view).or_default().pop();
+        }
+        Key::Char(c) => {
+            app.search.searches.entry(view).or_default().push(c);
+        }
+        _ => { return; }
+    }
+    keep_selections(app);
+    app.redraw = true;
+}
+
+/// Select the next or previous match in the current view, wrapping around at the ends. Hidden
+/// monitors are skipped like in the views.
+pub fn jump(app: &mut App, forward: bool) {
+    let view = app.view;
+    let search = app.search.get(view);
+    if search.is_empty() {
+        return;
+    }
+    let hide_monitors = app.hide_monitors;
+    match view {
+        AppView::SinkInputs    => jump_in(&mut app.sink_input_list, forward, |x| matches(x, &search)),
+        AppView::SourceOutputs => {
+            let source_list = &app.source_list;
+            jump_in(&mut app.source_output_list, forward, |x| {
+                let monitor = x.source_index == 0xffffffff || source_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false);
+                !(monitor && hide_monitors) && matches(x, &search)
+            });
+        }
+        AppView::Sinks         => jump_in(&mut app.sink_list, forward, |x| matches(x, &search)),
+        AppView::Sources       => jump_in(&mut app.source_list, forward, |x| !(x.is_monitor() && hide_monitors) && matches(x, &search)),
+        AppView::Cards         => jump_in(&mut app.card_list, forward, |x| matches(x, &search)),
+        AppView::Modules       => jump_in(&mut app.module_list, forward, |x| matches(x, &search)),
+    }
+    app.redraw = true;
+}
+
+fn jump_in<V, F>(list: &mut SelectingMap<u32, V>, forward: bool, filter: F)
+    where F: Fn(&V) -> bool {
+    let selected = match list.get_selected_key() {
+        Some(selected) => selected,
+        None => return,
+    };
+    let next = if forward {
+        list.filtered_next_key(selected, &filter).or_else(|| list.find_key(&filter))
+    } else {
+        list.filtered_prev_key(selected, &filter).or_else(|| list.rfind_key(&filter))
+    };
+    if let Some(next) = next {
+        list.select(next);
+    }
+}
+
+/// Make sure a matching entry is selected in every view, after a search or a list changed.
+/// Hidden monitors are skipped like in the views.
+pub fn keep_selections(app: &mut App) {
+    let hide_monitors = app.hide_monitors;
+
+    let search = app.search.get(AppView::SinkInputs);
+    keep_selection(&mut app.sink_input_list, |x| matches(x, &search));
+
+    let search = app.search.get(AppView::SourceOutputs);
+    let source_list = &app.source_list;
+    keep_selection(&mut app.source_output_list, |x| {
+        let monitor = x.source_index == 0xffffffff || source_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false);
+        !(monitor && hide_monitors) && matches(x, &search)
+    });
+
+    let search = app.search.get(AppView::Sinks);
+    keep_selection(&mut app.sink_list, |x| matches(x, &search));
+
+    let search = app.search.get(AppView::Sources);
+    keep_selection(&mut app.source_list, |x| !(x.is_monitor() && hide_monitors) && matches(x, &search));
+
+    let search = app.search.get(AppView::Cards);
+    keep_selection(&mut app.card_list, |x| matches(x, &search));
+
+    let search = app.search.get(AppView::Modules);
+    keep_selection(&mut app.module_list, |x| matches(x, &search));
+}
+
+/// Make sure a matching entry is selected, if there is one.
+fn keep_selection<V, F>(list: &mut SelectingMap<u32, V>, filter: F)
+    where F: Fn(&V) -> bool {
+    if let Some(item) = list.get_selected() {
+        if !filter(item) {
+            list.filtered_select_next_else_prev(filter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_app, test_sink};
+
+    #[test]
+    fn typing_selects_a_match() {
+        let mut app = test_app();
+        app.view = AppView::Sinks;
+        app.sink_list.select(1);
+        app.search.start(AppView::Sinks);
+        for c in "head".chars() {
+            handle_key_event(Key::Char(c), &mut app);
+        }
+        assert_eq!(app.sink_list.get_selected_key(), Some(2));
+    }
+
+    #[test]
+    fn removing_the_selected_match_selects_another_one() {
+        let mut app = test_app();
+        app.sink_list.update(3, test_sink(3, "amplifier"));
+        app.sink_list.update(4, test_sink(4, "headphones"));
+        app.view = AppView::Sinks;
+        app.search.start(AppView::Sinks);
+        for c in "head".chars() {
+            handle_key_event(Key::Char(c), &mut app);
+        }
+        app.sink_list.select(2);
+        // the next entry gets selected, which does not match
+        app.sink_list.remove(2);
+        assert_eq!(app.sink_list.get_selected_key(), Some(3));
+        keep_selections(&mut app);
+        assert_eq!(app.sink_list.get_selected_key(), Some(4));
+    }
+}
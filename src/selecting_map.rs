@@ -1,23 +1,32 @@
 use std::collections::BTreeMap;
-use std::ops::Bound;
 
 pub struct SelectingMap<K, V> {
     contents: BTreeMap<K, V>,
+    /// Keys in the order entries are shown, see `sort_by_cached_key`.
+    order: Vec<K>,
     selected_index: Option<K>,
     changed: bool,
+    /// Entries were added or updated since the last sort.
+    unsorted: bool,
 }
 
 impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
     pub fn new() -> SelectingMap<K, V> {
         SelectingMap {
             contents: BTreeMap::new(),
+            order: Vec::new(),
             selected_index: None,
             changed: false,
+            unsorted: false,
         }
     }
 
     pub fn update(&mut self, index: K, info: V) {
-        self.contents.insert(index, info);
+        if self.contents.insert(index, info).is_none() {
+            // new entries go by key until the next sort
+            let position = self.order.iter().position(|k| *k > index).unwrap_or(self.order.len());
+            self.order.insert(position, index);
+        }
 
         if self.selected_index.is_none() {
             assert_ne!(self.contents.len(), 0);
@@ -25,32 +34,27 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
         }
 
         self.changed = true;
+        self.unsorted = true;
     }
 
-    // pub fn next_key_filtered<F>(&self, key: K, filter: Option<F>) -> Option<K>
-    //     where F: Fn(&(&K, &V)) -> bool {
-    //     let filter: F = filter.unwrap_or(|x| true);
-    //     if let Some((next_key, _)) = self.contents.range((Bound::Excluded(key), Bound::Unbounded)).filter(filter).next() {
-    //         return Some(*next_key);
-    //     } else {
-    //         return None;
-    //     }
-    // }
+    /// Keys after and before the given one in display order. A key which is not in the map, like
+    /// one just removed, is placed by comparing keys.
+    fn split_order(&self, key: K) -> (&[K], &[K]) {
+        match self.order.iter().position(|k| *k == key) {
+            Some(position) => return (&self.order[position + 1..], &self.order[..position]),
+            None => {
+                let position = self.order.iter().position(|k| *k > key).unwrap_or(self.order.len());
+                return (&self.order[position..], &self.order[..position]);
+            }
+        }
+    }
 
     pub fn next_key(&self, key: K) -> Option<K> {
-        if let Some((next_key, _)) = self.contents.range((Bound::Excluded(key), Bound::Unbounded)).next() {
-            return Some(*next_key);
-        } else {
-            return None;
-        }
+        return self.split_order(key).0.first().copied();
     }
 
     pub fn prev_key(&self, key: K) -> Option<K> {
-        if let Some((prev_key, _)) = self.contents.range((Bound::Unbounded, Bound::Excluded(key))).next_back() {
-            return Some(*prev_key);
-        } else {
-            return None;
-        }
+        return self.split_order(key).1.last().copied();
     }
 
     pub fn select_next(&mut self) {
@@ -71,11 +75,22 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
         }
     }
 
+    pub fn select(&mut self, index: K) {
+        if self.contents.contains_key(&index) {
+            self.selected_index = Some(index);
+            self.changed = true;
+        }
+    }
+
     pub fn get_selected(&self) -> Option<&V> {
         let index = self.selected_index?;
         return Some(self.contents.get(&index).expect("Selected key is not in contents list"));
     }
 
+    pub fn get_selected_key(&self) -> Option<K> {
+        return self.selected_index;
+    }
+
     pub fn get_selected_mut(&mut self) -> Option<&mut V> {
         let index = self.selected_index?;
         self.changed = true;
@@ -86,15 +101,21 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
         return self.contents.len();
     }
 
-    pub fn values(&self) -> std::collections::btree_map::Values<K, V> {
-        return self.contents.values();
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + '_ {
+        return self.order.iter().map(move |k| self.contents.get(k).expect("Ordered key is not in contents list"));
     }
 
     pub fn get(&self, index: K) -> Option<&V> {
         return self.contents.get(&index);
     }
 
+    /// Remove an entry. A key which is not in the map is ignored, like a late remove event for an
+    /// entry which is already gone.
     pub fn remove(&mut self, index: K) {
+        if !self.contents.contains_key(&index) {
+            return;
+        }
+
         // set selected_index to a value that will still be there
         // an entry will be removed, so contents shouldn't be empty, so there should be a selection
         if index == self.selected_index.expect("No selected entry while removing one") {
@@ -112,9 +133,27 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
         }
 
         self.contents.remove(&index);
+        self.order.retain(|k| *k != index);
         self.changed = true;
     }
 
+    pub fn clear(&mut self) {
+        self.contents.clear();
+        self.order.clear();
+        self.selected_index = None;
+        self.changed = true;
+    }
+
+    pub fn find_key<F>(&self, mut predicate: F) -> Option<K>
+        where F: FnMut(&V) -> bool {
+        return self.order.iter().copied().find(|k| predicate(&self.contents[k]));
+    }
+
+    pub fn rfind_key<F>(&self, mut predicate: F) -> Option<K>
+        where F: FnMut(&V) -> bool {
+        return self.order.iter().copied().rev().find(|k| predicate(&self.contents[k]));
+    }
+
     pub fn get_changed(&self) -> bool {
         return self.changed;
     }
@@ -128,20 +167,12 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
 
     pub fn filtered_next_key<F>(&self, key: K, mut filter: F) -> Option<K>
         where F: FnMut(&V) -> bool {
-        if let Some((next_key, _)) = self.contents.range((Bound::Excluded(key), Bound::Unbounded)).filter(|(k,v)| filter(v)).next() {
-            return Some(*next_key);
-        } else {
-            return None;
-        }
+        return self.split_order(key).0.iter().copied().find(|k| filter(&self.contents[k]));
     }
 
     pub fn filtered_prev_key<F>(&self, key: K, mut filter: F) -> Option<K>
         where F: FnMut(&V) -> bool {
-        if let Some((prev_key, _)) = self.contents.range((Bound::Unbounded, Bound::Excluded(key))).filter(|(k,v)| filter(v)).next_back() {
-            return Some(*prev_key);
-        } else {
-            return None;
-        }
+        return self.split_order(key).1.iter().copied().rev().find(|k| filter(&self.contents[k]));
     }
 
     pub fn filtered_select_next<F>(&mut self, filter: F)
@@ -166,12 +197,12 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
 
     pub fn filtered_len<F>(&self, filter: F) -> usize
         where for<'r> F: FnMut(&'r &V) -> bool { // XXX
-        return self.contents.values().filter(filter).count();
+        return self.values().filter(filter).count();
     }
 
-    pub fn filtered_values<F>(&self, filter: F) -> std::iter::Filter<std::collections::btree_map::Values<K, V>, F>
-        where F: FnMut(&&V) -> bool { // XXX
-        return self.contents.values().filter(filter);
+    pub fn filtered_values<'a, F>(&'a self, filter: F) -> impl DoubleEndedIterator<Item = &'a V> + 'a
+        where F: FnMut(&&V) -> bool + 'a { // XXX
+        return self.values().filter(filter);
     }
 
     pub fn filtered_select_next_else_prev<F>(&mut self, filter: F)
@@ -186,4 +217,50 @@ impl<K: std::cmp::Ord + std::marker::Copy, V> SelectingMap<K, V> {
             }
         }
     }
+
+    /// Whether entries were added or updated since the last sort.
+    pub fn needs_sort(&self) -> bool {
+        return self.unsorted;
+    }
+
+    /// Order the entries for display by a key computed once per entry, entries with equal keys
+    /// staying in key order. The selection stays on the same entry.
+    pub fn sort_by_cached_key<T, F>(&mut self, mut key: F)
+        where T: Ord, F: FnMut(&V) -> T {
+        let contents = &self.contents;
+        let mut order: Vec<K> = contents.keys().copied().collect();
+        order.sort_by_cached_key(|k| key(&contents[k]));
+        self.unsorted = false;
+        if order != self.order {
+            self.order = order;
+            self.changed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_keeps_a_selection() {
+        let mut map = SelectingMap::new();
+        map.update(1, "a");
+        map.update(2, "b");
+        map.select(1);
+        map.remove(1);
+        assert_eq!(map.get_selected(), Some(&"b"));
+        map.remove(2);
+        assert_eq!(map.get_selected(), None);
+    }
+
+    #[test]
+    fn remove_of_missing_key_is_ignored() {
+        let mut map: SelectingMap<u32, &str> = SelectingMap::new();
+        map.remove(1);
+        map.update(2, "b");
+        map.remove(1);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_selected(), Some(&"b"));
+    }
 }
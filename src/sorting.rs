@@ -0,0 +1,184 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use pulse::def::{SinkState, SourceState};
+
+use crate::{App, AppView};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortMode {
+    Index,
+    Name,
+    /// The sink or source of a stream.
+    Device,
+    /// Running entries first.
+    State,
+    /// Loudest first.
+    Volume,
+}
+
+impl SortMode {
+    fn name(&self) -> &'static str {
+        match self {
+            SortMode::Index  => "index",
+            SortMode::Name   => "name",
+            SortMode::Device => "device",
+            SortMode::State  => "state",
+            SortMode::Volume => "volume",
+        }
+    }
+
+    /// The modes offered in a view, in the order they are cycled through.
+    fn modes(view: AppView) -> &'static [SortMode] {
+        match view {
+            AppView::SinkInputs | AppView::SourceOutputs => &[SortMode::Index, SortMode::Name, SortMode::Device, SortMode::State, SortMode::Volume],
+            AppView::Sinks | AppView::Sources            => &[SortMode::Index, SortMode::Name, SortMode::State, SortMode::Volume],
+            AppView::Cards | AppView::Modules            => &[SortMode::Index, SortMode::Name],
+        }
+    }
+}
+
+/// The sort mode of each view, by index if not set.
+#[derive(Default)]
+pub struct Sorting {
+    modes:   HashMap<AppView, SortMode>,
+    /// A mode changed, so all lists need sorting.
+    changed: bool,
+}
+
+impl Sorting {
+    pub fn get(&self, view: AppView) -> SortMode {
+        return self.modes.get(&view).copied().unwrap_or(SortMode::Index);
+    }
+
+    /// Switch a view to its next sort mode and return that.
+    pub fn cycle(&mut self, view: AppView) -> SortMode {
+        let modes = SortMode::modes(view);
+        let position = modes.iter().position(|x| *x == self.get(view)).unwrap_or(0);
+        let mode = modes[(position + 1) % modes.len()];
+        self.modes.insert(view, mode);
+        self.changed = true;
+        return mode;
+    }
+}
+
+/// Where the sort modes are kept between sessions.
+fn sorting_path() -> Option<PathBuf> {
+    return Some(crate::log::state_dir()?.join("sorting.toml"));
+}
+
+/// Load the sort modes of the last session. Anything unreadable is skipped.
+pub fn load() -> Sorting {
+    let mut sorting = Sorting::default();
+    let contents = match sorting_path().map(std::fs::read_to_string) {
+        Some(Ok(contents)) => contents,
+        _ => return sorting,
+    };
+    let file: HashMap<String, String> = match toml::from_str(&contents) {
+        Ok(file) => file,
+        Err(e) => {
+            crate::log::warn(&format!("ignoring saved sort modes: {}", e));
+            return sorting;
+        }
+    };
+    for (view_name, mode_name) in file.iter() {
+        let view = crate::config::parse_view(view_name);
+        let mode = view.and_then(|view| SortMode::modes(view).iter().copied().find(|x| x.name() == mode_name));
+        if let (Some(view), Some(mode)) = (view, mode) {
+            sorting.modes.insert(view, mode);
+        }
+    }
+    return sorting;
+}
+
+pub fn save(sorting: &Sorting) -> Result<(), String> {
+    let path = sorting_path().ok_or_else(|| String::from("no state directory"))?;
+    let file: HashMap<&str, &str> = sorting.modes.iter().map(|(view, mode)| (crate::config::view_name(*view), mode.name())).collect();
+    let contents = toml::to_string(&file).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    return std::fs::write(&path, contents).map_err(|e| e.to_string());
+}
+
+fn volume_key(volume: &pulse::volume::ChannelVolumes) -> Reverse<u32> {
+    return Reverse(volume.avg().0);
+}
+
+fn name_key(name: String) -> String {
+    return name.to_lowercase();
+}
+
+/// Order the lists by the sort mode of their view. A list is only sorted again if its entries
+/// changed since the last call, or all of them after a sort mode changed.
+pub fn sort_all(app: &mut App) {
+    let all = std::mem::take(&mut app.sorting.changed);
+    // streams sorted by device follow a renamed device
+    let sinks_changed = all || app.sink_list.needs_sort();
+    let sources_changed = all || app.source_list.needs_sort();
+
+    let sink_list = &app.sink_list;
+    let source_list = &app.source_list;
+    let sink_name = |index: u32| name_key(sink_list.get(index).map(|x| x.display_name()).unwrap_or_default());
+    let source_name = |index: u32| name_key(source_list.get(index).map(|x| x.display_name()).unwrap_or_default());
+
+    let mode = app.sorting.get(AppView::SinkInputs);
+    if app.sink_input_list.needs_sort() || (mode == SortMode::Device && sinks_changed) || all {
+        match mode {
+            SortMode::Name   => app.sink_input_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            SortMode::Device => app.sink_input_list.sort_by_cached_key(|x| sink_name(x.sink_index)),
+            SortMode::State  => app.sink_input_list.sort_by_cached_key(|x| x.corked),
+            SortMode::Volume => app.sink_input_list.sort_by_cached_key(|x| volume_key(&x.volume)),
+            SortMode::Index  => app.sink_input_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+
+    let mode = app.sorting.get(AppView::SourceOutputs);
+    if app.source_output_list.needs_sort() || (mode == SortMode::Device && sources_changed) || all {
+        match mode {
+            SortMode::Name   => app.source_output_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            SortMode::Device => app.source_output_list.sort_by_cached_key(|x| source_name(x.source_index)),
+            SortMode::State  => app.source_output_list.sort_by_cached_key(|x| x.corked),
+            SortMode::Volume => app.source_output_list.sort_by_cached_key(|x| volume_key(&x.volume)),
+            SortMode::Index  => app.source_output_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+
+    if sinks_changed {
+        match app.sorting.get(AppView::Sinks) {
+            SortMode::Name   => app.sink_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            SortMode::State  => app.sink_list.sort_by_cached_key(|x| x.state != SinkState::Running),
+            SortMode::Volume => app.sink_list.sort_by_cached_key(|x| volume_key(&x.volume)),
+            _                => app.sink_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+
+    if sources_changed {
+        match app.sorting.get(AppView::Sources) {
+            SortMode::Name   => app.source_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            SortMode::State  => app.source_list.sort_by_cached_key(|x| x.state != SourceState::Running),
+            SortMode::Volume => app.source_list.sort_by_cached_key(|x| volume_key(&x.volume)),
+            _                => app.source_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+
+    if app.card_list.needs_sort() || all {
+        match app.sorting.get(AppView::Cards) {
+            SortMode::Name => app.card_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            _              => app.card_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+
+    if app.module_list.needs_sort() || all {
+        match app.sorting.get(AppView::Modules) {
+            SortMode::Name => app.module_list.sort_by_cached_key(|x| name_key(x.display_name())),
+            _              => app.module_list.sort_by_cached_key(|x| x.index),
+        }
+    }
+}
+
+/// Describe a sort mode for the status line.
+pub fn describe(mode: SortMode) -> String {
+    return format!("Sorted by {}", mode.name());
+}
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+/// How long info messages stay in the status line.
+const INFO_TIMEOUT: Duration = Duration::from_secs(3);
+/// Errors stay longer, so they are not missed.
+const ERROR_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusKind {
+    Info,
+    Error,
+}
+
+/// The line below the tabs, showing the outcome of operations until it expires.
+#[derive(Default)]
+pub struct StatusLine {
+    message: Option<(String, StatusKind, Instant)>,
+}
+
+impl StatusLine {
+    pub fn info(&mut self, text: String) {
+        self.message = Some((text, StatusKind::Info, Instant::now() + INFO_TIMEOUT));
+    }
+
+    pub fn error(&mut self, text: String) {
+        self.message = Some((text, StatusKind::Error, Instant::now() + ERROR_TIMEOUT));
+    }
+
+    pub fn get(&self) -> Option<(&str, StatusKind)> {
+        return self.message.as_ref().map(|(text, kind, _)| (text.as_str(), *kind));
+    }
+
+    /// Remove the message if it expired, returns whether it did.
+    pub fn expire(&mut self) -> bool {
+        match self.message {
+            Some((_, _, expires)) if expires <= Instant::now() => {
+                self.message = None;
+                return true;
+            }
+            _ => {
+                return false;
+            }
+        }
+    }
+
+    /// How long until the current message expires.
+    pub fn expires_in(&self) -> Option<Duration> {
+        return self.message.as_ref().map(|(_, _, expires)| expires.saturating_duration_since(Instant::now()));
+    }
+}
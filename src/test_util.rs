@@ -0,0 +1,127 @@
+use pulse::proplist::Proplist;
+use pulse::time::MicroSeconds;
+use pulse::volume::ChannelVolumes;
+
+use crate::{App, CardEntry, ModuleEntry, ProfileInfo, SinkEntry, SinkInputEntry, SourceEntry, SourceOutputEntry};
+
+/// Build an entry of the given type, stereo at normal volume and without properties, with the
+/// fields that differ between the types given like in a struct literal.
+macro_rules! stereo_entry {
+    ($entry:ident { $($field:ident: $value:expr),* $(,)? }) => {{
+        let mut channel_map = pulse::channelmap::Map::default();
+        channel_map.init_stereo();
+        let mut volume = ChannelVolumes::default();
+        volume.set(2, pulse::volume::Volume::NORMAL);
+        $entry {
+            volume:      volume,
+            channel_map: channel_map,
+            mute:        false,
+            proplist:    Proplist::new().unwrap(),
+            sample_spec: pulse::sample::Spec { format: pulse::sample::Format::S16le, channels: 2, rate: 44100 },
+            latency:     MicroSeconds(20000),
+            $($field: $value),*
+        }
+    }};
+}
+
+pub fn test_sink(index: u32, name: &str) -> SinkEntry {
+    return stereo_entry!(SinkEntry {
+        index:             index,
+        name:              String::from(name),
+        description:       String::from(name),
+        state:             pulse::def::SinkState::Idle,
+        owner_module:      Some(index + 100),
+        monitor_source:    index,
+        ports:             Vec::new(),
+        active_port_index: None,
+        driver:            Some(String::from("module-test.c")),
+        card:              None,
+    });
+}
+
+pub fn test_source(index: u32, name: &str, monitor_of_sink: Option<u32>) -> SourceEntry {
+    return stereo_entry!(SourceEntry {
+        index:             index,
+        name:              String::from(name),
+        description:       String::from(name),
+        monitor_of_sink:   monitor_of_sink,
+        state:             pulse::def::SourceState::Idle,
+        owner_module:      Some(index + 200),
+        ports:             Vec::new(),
+        active_port_index: None,
+        driver:            Some(String::from("module-test.c")),
+        card:              None,
+    });
+}
+
+pub fn test_sink_input(index: u32, name: &str, sink_index: u32) -> SinkInputEntry {
+    return stereo_entry!(SinkInputEntry {
+        index:           index,
+        name:            String::from(name),
+        sink_index:      sink_index,
+        corked:          false,
+        has_volume:      true,
+        volume_writable: true,
+        owner_module:    None,
+        client:          Some(5),
+        buffer_latency:  MicroSeconds(10000),
+        driver:          Some(String::from("protocol-native.c")),
+    });
+}
+
+pub fn test_source_output(index: u32, name: &str, source_index: u32) -> SourceOutputEntry {
+    return stereo_entry!(SourceOutputEntry {
+        index:           index,
+        name:            String::from(name),
+        source_index:    source_index,
+        corked:          false,
+        has_volume:      true,
+        volume_writable: true,
+        owner_module:    None,
+        client:          Some(5),
+        buffer_latency:  MicroSeconds(10000),
+        driver:          Some(String::from("protocol-native.c")),
+    });
+}
+
+/// A card with the given profiles, the first one being active.
+pub fn test_card(index: u32, name: &str, profiles: &[&str]) -> CardEntry {
+    let profiles: Vec<ProfileInfo> = profiles.iter().enumerate().map(|(i, x)| ProfileInfo {
+        name:        format!("profile-{}", i),
+        description: String::from(*x),
+        n_sinks:     1,
+        n_sources:   1,
+        priority:    i as u32,
+        available:   true,
+    }).collect();
+    let active = if profiles.is_empty() { None } else { Some(0) };
+    return CardEntry {
+        index:                  index,
+        name:                   String::from(name),
+        proplist:               Proplist::new().unwrap(),
+        ports:                  Vec::new(),
+        profiles:               profiles,
+        active_profile_index:   active,
+        selected_profile_index: active,
+        owner_module:           Some(index + 300),
+        driver:                 Some(String::from("module-alsa-card.c")),
+    };
+}
+
+pub fn test_module(index: u32, name: &str, argument: Option<&str>) -> ModuleEntry {
+    return ModuleEntry {
+        index:    index,
+        name:     String::from(name),
+        argument: argument.map(String::from),
+        n_used:   None,
+        proplist: Proplist::new().unwrap(),
+    };
+}
+
+/// The app the view tests start from, with the sinks `speakers` (1) and `headset` (2).
+pub fn test_app() -> App {
+    let mut app = App::new(Default::default());
+    app.sink_list.update(1, test_sink(1, "speakers"));
+    app.sink_list.update(2, test_sink(2, "headset"));
+    return app;
+}
@@ -1,4 +1,4 @@
-use termion::event::Key;
+use termion::event::{Key, MouseButton};
 use ratatui::backend::TermionBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Margin};
 use ratatui::style::{Color, Modifier, Style};
@@ -6,20 +6,26 @@ use ratatui::widgets::{Block, Borders, Gauge, Widget, Paragraph};
 use ratatui::text::Text;
 use ratatui::Terminal;
 
-use pulse::context::Context;
+use crate::backend::Backend;
 use std::sync::atomic;
 use std::sync::{Arc, Mutex};
 
-use crate::App;
+use crate::{App, AppView};
+use crate::search;
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
 
 #[derive(Default)]
 pub struct ViewData {
     keybinding_popup_open: bool,
+    keybinding_scroll: usize,
+    scroll_offset: usize,
 }
 
 impl ViewData {
     pub fn open_keybinding_popup(&mut self) {
         self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
     }
 
     pub fn close_keybinding_popup(&mut self) {
@@ -33,32 +39,37 @@ pub fn entered(app: &mut App) {
 
 pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
-    let mut constraints: Vec<ratatui::layout::Constraint> = app.card_list.values().map(|card| Constraint::Length(2 + card.profiles.len() as u16)).collect();
-    constraints.push(Constraint::Min(0));
+    let search = app.search.get(AppView::Cards);
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(rect);
+    let heights: Vec<u16> = app.card_list.filtered_values(|x| search::matches(*x, &search)).map(|card| 2 + card.profiles.len() as u16).collect();
+    let selected_index = app.card_list.get_selected().map(|x| x.index);
+    let selected_position = app.card_list.filtered_values(|x| search::matches(*x, &search)).position(|x| Some(x.index) == selected_index);
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.card_view_data.scroll_offset);
 
-    for (i, card) in app.card_list.values().enumerate() {
+    for (i, card) in app.card_list.filtered_values(|x| search::matches(*x, &search)).enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
 
         let title = format!(" {} ", card.display_name());
 
         let title_style = if card.index == app.card_list.get_selected().expect("No selected entry while drawing").index {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(app.config.colors.highlight)
         } else {
             Style::default()
         };
 
+        app.click_targets.push((chunk, ClickTarget::Entry { index: card.index, volume_area: None }));
+
         let block = Block::bordered()
             .title(title)
             .title_style(title_style);
         // .border_style(Style::default().fg(Color::White))
         // .style(Style::default().bg(Color::Black))
-        let inner = block.inner(chunks[i]); // save inner rectangle size for list, as block.render
-                                            // consumes the block
-        block.render(chunks[i], frame.buffer_mut());
+        let inner = block.inner(chunk); // save inner rectangle size for list, as block.render
+                                        // consumes the block
+        block.render(chunk, frame.buffer_mut());
 
         let list = Layout::default()
             .direction(Direction::Vertical)
@@ -67,15 +78,17 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
             .split(inner);
 
         for (j, profile) in card.profiles.iter().enumerate() {
+            app.click_targets.push((list[j], ClickTarget::Profile { card: card.index, profile: j }));
+
             let mut style = Style::default();
             if let Some(selected_index) = card.selected_profile_index {
                 if selected_index == j {
-                    style = Style::default().fg(Color::Red)
+                    style = Style::default().fg(app.config.colors.popup_cursor)
                 }
             }
             if let Some(active_index) = card.active_profile_index {
                 if active_index == j {
-                    style = Style::default().fg(Color::Green)
+                    style = Style::default().fg(app.config.colors.popup_current)
                 }
             }
             Paragraph::new(Text::raw(format!(" {}", profile.display_name())))
@@ -88,61 +101,33 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
         //     .items(&profile_names)
         //     .select(card.selected_profile_index)
         //     // .style(Style::default().fg(Color::Yellow))
-        //     .highlight_style(Style::default().fg(Color::Green).modifier(Modifier::ITALIC))
+        //     .highlight_style(Style::default().fg(app.config.colors.popup_current).modifier(Modifier::ITALIC))
         //     .render(&mut frame, block.inner(chunks[i]));
     }
 
     if app.card_view_data.keybinding_popup_open {
-        draw_keybinding_popup(frame, rect, app);
-    }
-}
-
-pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
-
-    let keys = vec![
-        ( "F1 through F5", "Change tab"),
-        ( "Tab", "Cycle tabs"),
-        ( "q  crtl-c", "Quit"),
-        ( "?", "Hotkeys"),
-        ( "Esc", "Close popup"),
-        ( "j/down  k/up", "Movement"),
-        ( "+  -", "Select profile for current card"),
-        ( "Return", "Confirm profile"),
-    ];
-
-    let rect = rect.inner(&Margin::new(4, 4));
-    crate::draw::ClearingWidget::default()
-        .render(rect, frame.buffer_mut());
-
-    let block = Block::bordered().title(" Keybindings ");
-    let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
-                                   // the block
-    block.render(rect, frame.buffer_mut());
-
-    let list = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1); keys.len()])
-        .split(inner);
-
-    for (j, (key, desc)) in keys.iter().enumerate() {
-        Paragraph::new(Text::raw(format!(" {:^17} {}", key, desc)))
-                .render(list[j], frame.buffer_mut());
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::Cards, &mut app.card_view_data.keybinding_scroll);
     }
 }
 
-pub fn handle_key_event(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
     if app.card_view_data.keybinding_popup_open {
-        handle_key_event_keybinding_popup(key, app, context);
+        handle_key_event_keybinding_popup(key, app, backend);
     } else {
-        handle_key_event_main(key, app, context);
+        handle_key_event_main(key, app, backend);
     }
 }
 
-pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
-    match key {
-        Key::Char('?') => {
+    let action = match app.config.keymap.action(KeymapSection::Cards, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::Help => {
             app.card_view_data.open_keybinding_popup();
             app.redraw = true;
             return;
@@ -150,15 +135,17 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
         _ => {}
     }
 
-    if let Some(card) = app.card_list.get_selected_mut() {
-        match key {
-            Key::Char('j') | Key::Down => {
-                app.card_list.select_next();
+    // with a search that matches nothing, the selected entry is not shown
+    let search = app.search.get(AppView::Cards);
+    if let Some(card) = app.card_list.get_selected_mut().filter(|x| search::matches(&**x, &search)) {
+        match action {
+            Action::Down => {
+                app.card_list.filtered_select_next(|x| search::matches(x, &search));
             }
-            Key::Char('k') | Key::Up => {
-                app.card_list.select_prev();
+            Action::Up => {
+                app.card_list.filtered_select_prev(|x| search::matches(x, &search));
             }
-            Key::Char('+') => {
+            Action::NextProfile => {
                 if let Some(selected_profile_index) = card.selected_profile_index {
                     let new_index = selected_profile_index + 1;
                     let new_index = new_index.min(card.profiles.len() - 1);
@@ -167,7 +154,7 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
                     assert_eq!(card.profiles.len(), 0);
                 }
             }
-            Key::Char('-') => {
+            Action::PrevProfile => {
                 if let Some(selected_profile_index) = card.selected_profile_index {
                     let new_index = if selected_profile_index == 0 { 0 } else { selected_profile_index-1 };
                     card.selected_profile_index = Some(new_index);
@@ -175,9 +162,9 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
                     assert_eq!(card.profiles.len(), 0);
                 }
             }
-            Key::Char('\n') => {
+            Action::Confirm => {
                 if let Some(selected_profile_index) = card.selected_profile_index {
-                    context.introspect().set_card_profile_by_index(card.index, &card.profiles[selected_profile_index].name, None);
+                    backend.set_card_profile(card.index, &card.profiles[selected_profile_index].name);
                 }
             }
             _ => {}
@@ -185,12 +172,49 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, context: &Context) {
-    match key {
-        Key::Esc => {
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Cards, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
             app.card_view_data.close_keybinding_popup();
             app.redraw = true;
         }
+        Action::Down => {
+            app.card_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.card_view_data.keybinding_scroll = app.card_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.card_view_data.keybinding_popup_open || button != MouseButton::Left {
+        return;
+    }
+
+    match target {
+        ClickTarget::Entry { index, .. } => {
+            app.card_list.select(index);
+        }
+        ClickTarget::Profile { card, profile } => {
+            app.card_list.select(card);
+            if let Some(card) = app.card_list.get_selected_mut() {
+                if let Some(profile_info) = card.profiles.get(profile) {
+                    card.selected_profile_index = Some(profile);
+                    backend.set_card_profile(card.index, &profile_info.name);
+                }
+            }
+        }
         _ => {}
     }
 }
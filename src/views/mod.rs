@@ -3,3 +3,4 @@ pub mod source_outputs;
 pub mod sinks;
 pub mod sources;
 pub mod cards;
+pub mod modules;
@@ -0,0 +1,340 @@
+use termion::event::{Key, MouseButton};
+use ratatui::layout::{Rect, Margin};
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Widget, Paragraph};
+use ratatui::text::Text;
+
+use crate::backend::Backend;
+
+use crate::{App, AppView};
+use crate::search;
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
+
+#[derive(Default)]
+pub struct ViewData {
+    keybinding_popup_open: bool,
+    keybinding_scroll: usize,
+    scroll_offset: usize,
+    /// Module to unload once confirmed.
+    confirm_unload: Option<u32>,
+    /// Name and arguments of a module to load, while being typed.
+    load_prompt: Option<String>,
+}
+
+impl ViewData {
+    pub fn open_keybinding_popup(&mut self) {
+        self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
+    }
+
+    pub fn close_keybinding_popup(&mut self) {
+        self.keybinding_popup_open = false;
+    }
+
+    pub fn close_load_prompt(&mut self) {
+        self.load_prompt = None;
+    }
+
+    /// Whether keys are typed into the load prompt.
+    pub fn is_typing(&self) -> bool {
+        return self.load_prompt.is_some();
+    }
+}
+
+pub fn entered(app: &mut App) {
+    app.module_view_data.close_keybinding_popup();
+    app.module_view_data.confirm_unload = None;
+    app.module_view_data.close_load_prompt();
+}
+
+/// Split what was typed into the load prompt into module name and arguments.
+fn parse_load_prompt(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    return match text.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None                   => (text, ""),
+    };
+}
+
+/// A rectangle of the given size in the middle of `rect`, shrunk to fit.
+fn centered(rect: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(rect.width);
+    let height = height.min(rect.height);
+    return Rect {
+        x: rect.x + (rect.width - width) / 2,
+        y: rect.y + (rect.height - height) / 2,
+        width,
+        height,
+    };
+}
+
+pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
+
+    let search = app.search.get(AppView::Modules);
+
+    let heights: Vec<u16> = app.module_list.filtered_values(|x| search::matches(*x, &search)).map(|_| 3).collect();
+    let selected_index = app.module_list.get_selected().map(|x| x.index);
+    let selected_position = app.module_list.filtered_values(|x| search::matches(*x, &search)).position(|x| Some(x.index) == selected_index);
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.module_view_data.scroll_offset);
+
+    for (i, module) in app.module_list.filtered_values(|x| search::matches(*x, &search)).enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+
+        let title_style = if Some(module.index) == selected_index {
+            Style::default().fg(app.config.colors.highlight)
+        } else {
+            Style::default()
+        };
+
+        app.click_targets.push((chunk, ClickTarget::Entry { index: module.index, volume_area: None }));
+
+        let block = Block::bordered()
+            .title(format!(" {} ", module.display_name()))
+            .title_style(title_style);
+        let inner = block.inner(chunk);
+        block.render(chunk, frame.buffer_mut());
+
+        let mut line = format!(" #{}", module.index);
+        if let Some(n_used) = module.n_used {
+            line += &format!("  used: {}", n_used);
+        }
+        if let Some(argument) = module.argument.as_ref() {
+            line += &format!("  {}", argument);
+        }
+        Paragraph::new(Text::raw(line))
+            .render(inner, frame.buffer_mut());
+    }
+
+    if let Some(index) = app.module_view_data.confirm_unload {
+        draw_confirm_popup(frame, rect, app, index);
+    }
+
+    if let Some(text) = app.module_view_data.load_prompt.as_ref() {
+        let rect = centered(rect.inner(&Margin::new(4, 0)), rect.width, 3);
+        crate::draw::ClearingWidget::default()
+            .render(rect, frame.buffer_mut());
+
+        let block = Block::bordered().title(" Load Module (name and arguments) ");
+        let inner = block.inner(rect);
+        block.render(rect, frame.buffer_mut());
+        Paragraph::new(Text::raw(format!(" {}_", text)))
+            .render(inner, frame.buffer_mut());
+    }
+
+    if app.module_view_data.keybinding_popup_open {
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::Modules, &mut app.module_view_data.keybinding_scroll);
+    }
+}
+
+fn draw_confirm_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App, index: u32) {
+
+    let name = match app.module_list.get(index) {
+        Some(module) => module.display_name(),
+        None => { app.module_view_data.confirm_unload = None; return; }
+    };
+
+    let rect = centered(rect.inner(&Margin::new(4, 0)), rect.width, 4);
+    crate::draw::ClearingWidget::default()
+        .render(rect, frame.buffer_mut());
+
+    let block = Block::bordered().title(" Unload Module ");
+    let inner = block.inner(rect);
+    block.render(rect, frame.buffer_mut());
+    Paragraph::new(Text::raw(format!(" Unload {} (#{})?\n Return to confirm, Esc to cancel", name, index)))
+        .render(inner, frame.buffer_mut());
+}
+
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.module_view_data.load_prompt.is_some() {
+        handle_key_event_load_prompt(key, app, backend);
+    } else if app.module_view_data.confirm_unload.is_some() {
+        handle_key_event_confirm_popup(key, app, backend);
+    } else if app.module_view_data.keybinding_popup_open {
+        handle_key_event_keybinding_popup(key, app, backend);
+    } else {
+        handle_key_event_main(key, app, backend);
+    }
+}
+
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.action(KeymapSection::Modules, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::Help => {
+            app.module_view_data.open_keybinding_popup();
+            app.redraw = true;
+            return;
+        }
+        Action::LoadModule => {
+            app.module_view_data.load_prompt = Some(String::new());
+            app.redraw = true;
+            return;
+        }
+        _ => {}
+    }
+
+    // with a search that matches nothing, the selected entry is not shown
+    let search = app.search.get(AppView::Modules);
+    if let Some(module) = app.module_list.get_selected().filter(|x| search::matches(*x, &search)) {
+        match action {
+            Action::Down => {
+                app.module_list.filtered_select_next(|x| search::matches(x, &search));
+            }
+            Action::Up => {
+                app.module_list.filtered_select_prev(|x| search::matches(x, &search));
+            }
+            Action::UnloadModule => {
+                app.module_view_data.confirm_unload = Some(module.index);
+                app.redraw = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keys typed into the load prompt: return loads the module, escape cancels.
+pub fn handle_key_event_load_prompt(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let text = match app.module_view_data.load_prompt.as_mut() {
+        Some(text) => text,
+        None => return,
+    };
+
+    match key {
+        Key::Char('\n') => {
+            let (name, argument) = parse_load_prompt(text);
+            if !name.is_empty() {
+                backend.load_module(name, argument);
+            }
+            app.module_view_data.close_load_prompt();
+        }
+        Key::Esc       => { app.module_view_data.close_load_prompt(); }
+        Key::Backspace => { text.pop(); }
+        Key::Char(c)   => { text.push(c); }
+        _ => {}
+    }
+    app.redraw = true;
+}
+
+pub fn handle_key_event_confirm_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Modules, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::Confirm => {
+            if let Some(index) = app.module_view_data.confirm_unload.take() {
+                backend.unload_module(index);
+            }
+            app.redraw = true;
+        }
+        Action::ClosePopup => {
+            app.module_view_data.confirm_unload = None;
+            app.redraw = true;
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Modules, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
+            app.module_view_data.close_keybinding_popup();
+            app.redraw = true;
+        }
+        Action::Down => {
+            app.module_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.module_view_data.keybinding_scroll = app.module_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    let data = &app.module_view_data;
+    if data.keybinding_popup_open || data.confirm_unload.is_some() || data.load_prompt.is_some() || button != MouseButton::Left {
+        return;
+    }
+
+    if let ClickTarget::Entry { index, .. } = target {
+        app.module_list.select(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Call, FakeBackend};
+    use crate::test_util::test_module;
+
+    fn app() -> App {
+        let mut app = App::new(Default::default());
+        app.module_list.update(40, test_module(40, "module-alsa-card", Some("device_id=0")));
+        app.module_list.update(41, test_module(41, "module-null-sink", None));
+        return app;
+    }
+
+    fn type_text(text: &str, app: &mut App, backend: &mut FakeBackend) {
+        for c in text.chars() {
+            handle_key_event(Key::Char(c), app, backend);
+        }
+    }
+
+    #[test]
+    fn unload_needs_confirmation() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        handle_key_event(Key::Char('D'), &mut app, &mut backend);
+        handle_key_event(Key::Esc, &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![]);
+        handle_key_event(Key::Char('D'), &mut app, &mut backend);
+        handle_key_event(Key::Char('\n'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::UnloadModule(41)]);
+    }
+
+    #[test]
+    fn load_splits_name_and_arguments() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('a'), &mut app, &mut backend);
+        type_text("module-null-sinkk", &mut app, &mut backend);
+        handle_key_event(Key::Backspace, &mut app, &mut backend);
+        type_text("  sink_name=test rate=48000 ", &mut app, &mut backend);
+        handle_key_event(Key::Char('\n'), &mut app, &mut backend);
+        assert!(!app.module_view_data.is_typing());
+        assert_eq!(backend.calls, vec![Call::LoadModule(String::from("module-null-sink"), String::from("sink_name=test rate=48000"))]);
+    }
+
+    #[test]
+    fn empty_load_prompt_loads_nothing() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('a'), &mut app, &mut backend);
+        type_text("  ", &mut app, &mut backend);
+        handle_key_event(Key::Char('\n'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![]);
+    }
+}
@@ -1,4 +1,4 @@
-use termion::event::Key;
+use termion::event::{Key, MouseButton};
 use ratatui::backend::TermionBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Margin};
 use ratatui::style::{Color, Modifier, Style};
@@ -6,17 +6,26 @@ use ratatui::widgets::{Block, Borders, Gauge, Widget, Paragraph};
 use ratatui::text::Text;
 use ratatui::Terminal;
 
-use pulse::context::Context;
+use crate::backend::Backend;
 use std::sync::atomic;
 use std::sync::{Arc, Mutex};
 
-use crate::App;
+use crate::{App, AppView};
+use crate::search;
+use crate::groups::{self, Groups, StreamRow};
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
+use crate::channels::{ChannelState, VolumeChange, BalanceKind, BALANCE_STEP, has_balance_controls};
 
 #[derive(Default)]
 pub struct ViewData {
     sink_popup_open: bool,
     keybinding_popup_open: bool,
+    keybinding_scroll: usize,
     sink_index_selected: u32,
+    channels: ChannelState,
+    groups: Groups,
+    scroll_offset: usize,
 }
 
 impl ViewData {
@@ -31,6 +40,7 @@ impl ViewData {
 
     pub fn open_keybinding_popup(&mut self) {
         self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
     }
 
     pub fn close_keybinding_popup(&mut self) {
@@ -43,23 +53,56 @@ pub fn entered(app: &mut App) {
     app.sink_input_view_data.close_keybinding_popup();
 }
 
+/// The rows shown: streams matching the search, grouped by application if enabled.
+fn rows(app: &App) -> Vec<StreamRow> {
+    let search = app.search.get(AppView::SinkInputs);
+    let streams = app.sink_input_list.filtered_values(|x| search::matches(*x, &search)).map(|x| (x.index, &x.proplist));
+    return app.sink_input_view_data.groups.rows(app.group_streams, streams);
+}
+
+/// The streams an action on the selected row applies to.
+fn selected_members(app: &App) -> Vec<u32> {
+    let selected = match app.sink_input_list.get_selected() {
+        Some(stream) => stream.index,
+        None => return Vec::new(),
+    };
+    return groups::row_of(&rows(app), selected).map(|x| x.members.clone()).unwrap_or_else(|| vec![selected]);
+}
+
 pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
-    let mut constraints = vec![Constraint::Length(3); app.sink_input_list.len()];
-    constraints.push(Constraint::Min(0));
+    let search = app.search.get(AppView::SinkInputs);
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(rect);
+    let rows = rows(app);
+    let selected_index = app.sink_input_list.get_selected().map(|x| x.index);
+    let heights: Vec<u16> = rows.iter()
+        .map(|row| {
+            let x = app.sink_input_list.get(row.members[0]).expect("Stream of a row is not in the list");
+            if row.group.is_some() {
+                return 3;
+            }
+            let balance_height = if has_balance_controls(&x.channel_map) { 1 } else { 0 };
+            app.sink_input_view_data.channels.entry_height(Some(x.index) == selected_index, x.volume.len()) + balance_height
+        })
+        .collect();
+    let selected_position = selected_index.and_then(|index| rows.iter().position(|x| x.contains(index)));
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.sink_input_view_data.scroll_offset);
+
+    for (i, row) in rows.iter().enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+
+        if row.group.is_some() {
+            draw_group(frame, chunk, row, Some(i) == selected_position, app);
+            continue;
+        }
+        let stream = app.sink_input_list.get(row.members[0]).expect("Stream of a row is not in the list");
 
-    for (i, stream) in app.sink_input_list.values().enumerate() {
         let vol = stream.volume.avg();
         let volume_ratio = vol.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
-        let mut label = format!("{:.0}%", volume_ratio * 100f64);
-        if stream.mute {
-            label += " (muted)";
-        }
+        let label = format!("{:.0}%", volume_ratio * 100f64);
 
         let display_name = stream.display_name();
         let sink_name = app.sink_list.get(stream.sink_index).map(|s| s.display_name()).unwrap_or(String::from("?"));
@@ -67,20 +110,39 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
         let invalid = stream.mute || !stream.has_volume || stream.corked;
 
-        let color = if stream.index == app.sink_input_list.get_selected().expect("No selected entry while drawing").index {
-            if invalid { Color::Gray } else { Color::Green }
+        let is_selected = stream.index == app.sink_input_list.get_selected().expect("No selected entry while drawing").index;
+
+        let color = if is_selected {
+            if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
         } else if invalid {
-            Color::DarkGray
+            app.config.colors.inactive
         } else {
-            Color::Yellow
+            app.config.colors.normal
         };
 
-        Gauge::default()
-            .block(Block::bordered().title(name))
-            .gauge_style(Style::default().fg(color))
-            .ratio(volume_ratio.min(1.0))
-            .label(&label)
-            .render(chunks[i], frame.buffer_mut());
+        let mut gauge_rect = chunk;
+        if has_balance_controls(&stream.channel_map) && gauge_rect.height > 0 {
+            gauge_rect.height -= 1;
+            let slider_rect = Rect::new(gauge_rect.x, gauge_rect.bottom(), gauge_rect.width, 1);
+            crate::draw::draw_balance_sliders(frame.buffer_mut(), slider_rect, &stream.volume, &stream.channel_map, &app.config.colors);
+        }
+
+        let channels_expanded = app.sink_input_view_data.channels.entry_height(is_selected, stream.volume.len()) > 3;
+        let volume_area = if channels_expanded { None } else { Some(gauge_rect.inner(&Margin::new(1, 1))) };
+        app.click_targets.push((chunk, ClickTarget::Entry { index: stream.index, volume_area }));
+
+        let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: stream.mute };
+        if channels_expanded {
+            crate::draw::draw_channel_gauges(frame.buffer_mut(), gauge_rect, Block::bordered().title(name),
+                &stream.volume, &stream.channel_map, &app.sink_input_view_data.channels, style);
+        } else {
+            crate::draw::draw_volume_gauge(frame.buffer_mut(), gauge_rect, Some(Block::bordered().title(name)),
+                volume_ratio, label, style);
+        }
+
+        if let Some(peak) = app.meters.sink_input_peak(stream.index) {
+            crate::draw::draw_peak_meter(frame.buffer_mut(), gauge_rect, peak, style);
+        }
     }
 
     if app.sink_input_view_data.sink_popup_open {
@@ -88,7 +150,51 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
     }
 
     if app.sink_input_view_data.keybinding_popup_open {
-        draw_keybinding_popup(frame, rect, app);
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::SinkInputs, &mut app.sink_input_view_data.keybinding_scroll);
+    }
+}
+
+/// A collapsed group, with the average volume of its streams.
+fn draw_group(frame: &mut ratatui::terminal::Frame, chunk: Rect, row: &StreamRow, is_selected: bool, app: &mut App) {
+
+    let stream_list = &app.sink_input_list;
+    let streams: Vec<&crate::SinkInputEntry> = row.members.iter().filter_map(|x| stream_list.get(*x)).collect();
+    let group = row.group.as_deref().unwrap_or("?");
+
+    let vol = streams.iter().map(|x| x.volume.avg().0 as f64).sum::<f64>() / streams.len() as f64;
+    let volume_ratio = vol / pulse::volume::Volume::NORMAL.0 as f64;
+    let label = format!("{:.0}%", volume_ratio * 100f64);
+
+    let mut sinks: Vec<u32> = streams.iter().map(|x| x.sink_index).collect();
+    sinks.sort();
+    sinks.dedup();
+    let sink_name = match sinks[..] {
+        [sink_index] => app.sink_list.get(sink_index).map(|s| s.display_name()).unwrap_or(String::from("?")),
+        _ => String::from("several sinks"),
+    };
+    let name = format!(" {} ({} streams)  ->  {} ", group, streams.len(), sink_name);
+
+    let invalid = streams.iter().all(|x| x.mute || !x.has_volume || x.corked);
+
+    let color = if is_selected {
+        if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
+    } else if invalid {
+        app.config.colors.inactive
+    } else {
+        app.config.colors.normal
+    };
+
+    let volume_area = Some(chunk.inner(&Margin::new(1, 1)));
+    app.click_targets.push((chunk, ClickTarget::Entry { index: row.members[0], volume_area }));
+
+    let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: streams.iter().all(|x| x.mute) };
+    crate::draw::draw_volume_gauge(frame.buffer_mut(), chunk, Some(Block::bordered().title(name)),
+        volume_ratio, label, style);
+
+    let meters = &app.meters;
+    let peak = streams.iter().filter_map(|x| meters.sink_input_peak(x.index)).fold(None, |a: Option<f32>, b| Some(a.map_or(b, |a| a.max(b))));
+    if let Some(peak) = peak {
+        crate::draw::draw_peak_meter(frame.buffer_mut(), chunk, peak, style);
     }
 }
 
@@ -116,79 +222,48 @@ pub fn draw_sink_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &m
     for (j, sink) in app.sink_list.values().enumerate() {
         let mut style = Style::default();
         if app.sink_input_view_data.sink_index_selected == sink.index {
-            style = Style::default().fg(Color::Red)
+            style = Style::default().fg(app.config.colors.popup_cursor)
         }
         if focused_stream.sink_index == sink.index {
-            style = Style::default().fg(Color::Green)
+            style = Style::default().fg(app.config.colors.popup_current)
         }
+        app.click_targets.push((list[j], ClickTarget::PopupEntry(sink.index)));
         Paragraph::new(Text::raw(format!(" {} ", sink.display_name())))
             .style(style)
             .render(list[j], frame.buffer_mut());
         }
 }
 
-pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
-
-    let keys = vec![
-        ( "F1 through F5", "Change tab"),
-        ( "Tab", "Cycle tabs"),
-        ( "q  crtl-c", "Quit"),
-        ( "?", "Hotkeys"),
-        ( "Esc", "Close popup"),
-        ( "j/down  k/up", "Movement"),
-        ( "^  1 through 0", "Audio level shortcut"),
-        ( "m", "Toggle mute"),
-        ( "h  l", "Volume down / up"),
-        ( "H  L", "Volume down / up (10% steps)"),
-        ( "ctrl-H  ctrl-L", "Volume 0% / 100%"),
-        ( "i  return", "Choose sink for selected stream"),
-        ( "K", "Kill stream"),
-        ( "ctrl-k", "Kill all non-running streams"),
-    ];
-
-    let rect = rect.inner(&Margin::new(4, 4));
-    crate::draw::ClearingWidget::default()
-        .render(rect, frame.buffer_mut());
-
-    let block = Block::bordered().title(" Keybindings ");
-    let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
-                                   // the block
-    block.render(rect, frame.buffer_mut());
-
-    let list = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1); keys.len()])
-        .split(inner);
-
-    for (j, (key, desc)) in keys.iter().enumerate() {
-        Paragraph::new(Text::raw(format!(" {:^17} {}", key, desc)))
-                .render(list[j], frame.buffer_mut());
-    }
-}
-
-pub fn handle_key_event(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
     if app.sink_input_view_data.keybinding_popup_open {
-        handle_key_event_keybinding_popup(key, app, context);
+        handle_key_event_keybinding_popup(key, app, backend);
     } else if app.sink_input_view_data.sink_popup_open {
-        handle_key_event_sink_popup(key, app, context);
+        handle_key_event_sink_popup(key, app, backend);
     } else {
-        handle_key_event_main(key, app, context);
+        handle_key_event_main(key, app, backend);
     }
 }
 
-pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
-    match key {
-        Key::Ctrl('k') => {
-            for stream in app.sink_input_list.values() {
+    let action = match app.config.keymap.action(KeymapSection::SinkInputs, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    let search = app.search.get(AppView::SinkInputs);
+
+    match action {
+        Action::KillInactive => {
+            for stream in app.sink_input_list.filtered_values(|x| search::matches(*x, &search)) {
                 if stream.corked {
-                    context.introspect().kill_sink_input(stream.index, |_| {});
+                    backend.kill_sink_input(stream.index);
                 }
             }
             return;
         }
-        Key::Char('?') => {
+        Action::Help => {
             app.sink_input_view_data.open_keybinding_popup();
             app.redraw = true;
             return;
@@ -196,82 +271,125 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
         _ => {}
     }
 
-    if let Some(stream) = app.sink_input_list.get_selected() {
-        match key {
-            Key::Char('j') | Key::Down => {
-                app.sink_input_list.select_next();
+    // with a search that matches nothing, the selected entry is not shown
+    if let Some(stream) = app.sink_input_list.get_selected().filter(|x| search::matches(*x, &search)) {
+        let rows = rows(app);
+        let in_group = groups::row_of(&rows, stream.index).map(|x| x.group.is_some()).unwrap_or(false);
+        match action {
+            Action::Down => {
+                if let Some(index) = groups::next_row(&rows, stream.index, true) {
+                    app.sink_input_list.select(index);
+                }
             }
-            Key::Char('k') | Key::Up => {
-                app.sink_input_list.select_prev();
+            Action::Up => {
+                if let Some(index) = groups::next_row(&rows, stream.index, false) {
+                    app.sink_input_list.select(index);
+                }
             }
-            Key::Char('m') => {
-                context.introspect().set_sink_input_mute(stream.index, !stream.mute, None);
+            Action::ToggleGroups => {
+                app.group_streams = !app.group_streams;
+                app.redraw = true;
             }
-            Key::Char('K') => {
-                context.introspect().kill_sink_input(stream.index, |_| {});
+            Action::ExpandGroup => {
+                if let Some(key) = groups::group_key(&stream.proplist) {
+                    app.sink_input_view_data.groups.toggle_expanded(key);
+                    app.redraw = true;
+                }
             }
-            Key::Char('h') | Key::Left => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+            _ if in_group => {
+                let members = selected_members(app);
+                handle_key_event_group(action, &members, app, backend);
             }
-            Key::Char('l') | Key::Right => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+            Action::ToggleMute => {
+                backend.set_sink_input_mute(stream.index, !stream.mute);
             }
-            Key::Char('H') => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+            Action::Kill => {
+                backend.kill_sink_input(stream.index);
             }
-            Key::Char('L') => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+            Action::VolumeDown => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Decrease(app.config.volume_step_small), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
             }
-            Key::Ctrl('h') => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.mute(new_vol.len());
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+            Action::VolumeUp => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Increase(app.config.volume_step_small), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
             }
-            Key::Ctrl('l') => {
-                let mut new_vol = stream.volume.clone();
-                new_vol.reset(new_vol.len());
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
-            }
-            Key::Char('^')
-                | Key::Char('1')
-                | Key::Char('2')
-                | Key::Char('3')
-                | Key::Char('4')
-                | Key::Char('5')
-                | Key::Char('6')
-                | Key::Char('7')
-                | Key::Char('8')
-                | Key::Char('9')
-                | Key::Char('0') => {
-
-                let factor = match key {
-                    Key::Char('^') => 0,
-                    Key::Char('1') => 1,
-                    Key::Char('2') => 2,
-                    Key::Char('3') => 3,
-                    Key::Char('4') => 4,
-                    Key::Char('5') => 5,
-                    Key::Char('6') => 6,
-                    Key::Char('7') => 7,
-                    Key::Char('8') => 8,
-                    Key::Char('9') => 9,
-                    Key::Char('0') => 10,
-                    _ => 0,
-                };
+            Action::VolumeDownBig => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Decrease(app.config.volume_step_big), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            Action::VolumeUpBig => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Increase(app.config.volume_step_big), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            Action::VolumeMin => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::MUTED.0), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            Action::VolumeNormal => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            Action::ToggleChannels => {
+                app.sink_input_view_data.channels.toggle_expanded();
+                app.redraw = true;
+            }
+            Action::LockChannels => {
+                app.sink_input_view_data.channels.toggle_locked();
+                app.redraw = true;
+            }
+            Action::NextChannel => {
+                app.sink_input_view_data.channels.select_next(stream.volume.len());
+                app.redraw = true;
+            }
+            Action::PrevChannel => {
+                app.sink_input_view_data.channels.select_prev();
+                app.redraw = true;
+            }
+            Action::BalanceLeft => {
+                if let Some(new_vol) = BalanceKind::Balance.change(&stream.volume, &stream.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::BalanceRight => {
+                if let Some(new_vol) = BalanceKind::Balance.change(&stream.volume, &stream.channel_map, BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::FadeRear => {
+                if let Some(new_vol) = BalanceKind::Fade.change(&stream.volume, &stream.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::FadeFront => {
+                if let Some(new_vol) = BalanceKind::Fade.change(&stream.volume, &stream.channel_map, BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::LfeDown => {
+                if let Some(new_vol) = BalanceKind::Lfe.change(&stream.volume, &stream.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::LfeUp => {
+                if let Some(new_vol) = BalanceKind::Lfe.change(&stream.volume, &stream.channel_map, BALANCE_STEP) {
+                    backend.set_sink_input_volume(stream.index, &new_vol);
+                }
+            }
+            Action::ResetBalance => {
                 let mut new_vol = stream.volume.clone();
-                new_vol.set(new_vol.len(), pulse::volume::Volume{0: pulse::volume::Volume::NORMAL.0 / 10 * factor});
-                context.introspect().set_sink_input_volume(stream.index, &new_vol, None);
+                for kind in BalanceKind::ALL.iter() {
+                    if let Some(v) = kind.set(&new_vol, &stream.channel_map, 0.0) {
+                        new_vol = v;
+                    }
+                }
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            Action::VolumeLevel(factor) => {
+                let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor), app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
             }
-            Key::Char('\n') |
-            Key::Char('i') => {
+            Action::ChooseDevice => {
                 app.sink_input_view_data.open_sink_popup(stream);
                 app.redraw = true;
             }
@@ -280,7 +398,56 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_sink_popup(key: Key, app: &mut App, context: &Context) {
+/// Actions on a collapsed group apply to all of its streams, a volume change keeps the level of
+/// the streams relative to each other.
+fn handle_key_event_group(action: Action, members: &[u32], app: &mut App, backend: &mut dyn Backend) {
+
+    let stream_list = &app.sink_input_list;
+    let streams: Vec<&crate::SinkInputEntry> = members.iter().filter_map(|x| stream_list.get(*x)).collect();
+
+    let change = match action {
+        Action::ToggleMute => {
+            let mute = !streams.iter().all(|x| x.mute);
+            for stream in streams.iter() {
+                backend.set_sink_input_mute(stream.index, mute);
+            }
+            return;
+        }
+        Action::Kill => {
+            for stream in streams.iter() {
+                backend.kill_sink_input(stream.index);
+            }
+            return;
+        }
+        Action::ChooseDevice => {
+            app.sink_input_view_data.open_sink_popup(streams[0]);
+            app.redraw = true;
+            return;
+        }
+        Action::VolumeDown          => VolumeChange::Decrease(app.config.volume_step_small),
+        Action::VolumeUp            => VolumeChange::Increase(app.config.volume_step_small),
+        Action::VolumeDownBig       => VolumeChange::Decrease(app.config.volume_step_big),
+        Action::VolumeUpBig         => VolumeChange::Increase(app.config.volume_step_big),
+        Action::VolumeMin           => VolumeChange::Set(pulse::volume::Volume::MUTED.0),
+        Action::VolumeNormal        => VolumeChange::Set(pulse::volume::Volume::NORMAL.0),
+        Action::VolumeLevel(factor) => VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor),
+        _ => return,
+    };
+
+    // the channels of a group are never expanded, so all channels change together
+    let channels = ChannelState::default();
+    for stream in streams.iter() {
+        let new_vol = channels.change_volume(&stream.volume, change, app.max_volume);
+        backend.set_sink_input_volume(stream.index, &new_vol);
+    }
+}
+
+pub fn handle_key_event_sink_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::SinkInputs, key) {
+        Some(action) => action,
+        None => return,
+    };
 
     let stream = match app.sink_input_list.get_selected() {
         Some(stream) => stream,
@@ -290,23 +457,25 @@ pub fn handle_key_event_sink_popup(key: Key, app: &mut App, context: &Context) {
         }
     };
 
-    match key {
-        Key::Esc => {
+    match action {
+        Action::ClosePopup => {
             app.sink_input_view_data.close_sink_popup();
             app.redraw = true;
         }
-        Key::Char('\n') => {
-            context.introspect().move_sink_input_by_index(stream.index, app.sink_input_view_data.sink_index_selected, None);
+        Action::Confirm => {
+            for index in selected_members(app) {
+                backend.move_sink_input(index, app.sink_input_view_data.sink_index_selected);
+            }
             app.sink_input_view_data.close_sink_popup();
             app.redraw = true;
         }
-        Key::Char('j') | Key::Down => {
+        Action::Down => {
             if let Some(k) = app.sink_list.next_key(app.sink_input_view_data.sink_index_selected) {
                 app.sink_input_view_data.sink_index_selected = k;
                 app.redraw = true;
             }
         }
-        Key::Char('k') | Key::Up => {
+        Action::Up => {
             if let Some(k) = app.sink_list.prev_key(app.sink_input_view_data.sink_index_selected) {
                 app.sink_input_view_data.sink_index_selected = k;
                 app.redraw = true;
@@ -316,12 +485,167 @@ pub fn handle_key_event_sink_popup(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, context: &Context) {
-    match key {
-        Key::Esc => {
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::SinkInputs, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
             app.sink_input_view_data.close_keybinding_popup();
             app.redraw = true;
         }
+        Action::Down => {
+            app.sink_input_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.sink_input_view_data.keybinding_scroll = app.sink_input_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
         _ => {}
     }
 }
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.sink_input_view_data.keybinding_popup_open {
+        return;
+    }
+
+    if app.sink_input_view_data.sink_popup_open {
+        if let ClickTarget::PopupEntry(index) = target {
+            for stream in selected_members(app) {
+                backend.move_sink_input(stream, index);
+            }
+            app.sink_input_view_data.close_sink_popup();
+            app.redraw = true;
+        }
+        return;
+    }
+
+    if let ClickTarget::Entry { index, volume_area } = target {
+        app.sink_input_list.select(index);
+
+        let stream = match app.sink_input_list.get(index) {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        let change = match button {
+            MouseButton::Left => match volume_area {
+                Some(area) if area.contains(position) => VolumeChange::Set(crate::draw::volume_at(area, position.x, app.max_volume)),
+                _ => return,
+            },
+            MouseButton::WheelUp   => VolumeChange::Increase(app.config.volume_step_small),
+            MouseButton::WheelDown => VolumeChange::Decrease(app.config.volume_step_small),
+            _ => return,
+        };
+        let members = selected_members(app);
+        if members.len() > 1 {
+            let stream_list = &app.sink_input_list;
+            for stream in members.iter().filter_map(|x| stream_list.get(*x)) {
+                let new_vol = ChannelState::default().change_volume(&stream.volume, change, app.max_volume);
+                backend.set_sink_input_volume(stream.index, &new_vol);
+            }
+            return;
+        }
+        let new_vol = app.sink_input_view_data.channels.change_volume(&stream.volume, change, app.max_volume);
+        backend.set_sink_input_volume(stream.index, &new_vol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Call, FakeBackend};
+    use crate::test_util::{test_app, test_sink_input};
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.sink_input_list.update(10, test_sink_input(10, "music", 1));
+        return app;
+    }
+
+    #[test]
+    fn volume_is_capped_at_max_volume() {
+        let mut app = app();
+        // the stream is at 100%, a big step up would take it to 110%
+        app.max_volume = crate::volume_from_percent(102);
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('L'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::SetSinkInputVolume(10, 102)]);
+    }
+
+    #[test]
+    fn sink_popup_moves_stream() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('i'), &mut app, &mut backend);
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        handle_key_event(Key::Char('\n'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::MoveSinkInput(10, 2)]);
+    }
+
+    #[test]
+    fn kill_stream() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('K'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::KillSinkInput(10)]);
+    }
+
+    /// Two streams of firefox and one of mpv, grouped by application.
+    fn grouped_app() -> App {
+        let mut app = app();
+        for (index, binary) in [(11, "firefox"), (12, "firefox"), (13, "mpv")].iter() {
+            let mut stream = test_sink_input(*index, "stream", 1);
+            stream.proplist.set_str("application.process.binary", binary).unwrap();
+            app.sink_input_list.update(*index, stream);
+        }
+        app.group_streams = true;
+        return app;
+    }
+
+    #[test]
+    fn group_mutes_all_streams() {
+        let mut app = grouped_app();
+        app.sink_input_list.select(11);
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('m'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::SetSinkInputMute(11, true), Call::SetSinkInputMute(12, true)]);
+    }
+
+    #[test]
+    fn group_is_one_row() {
+        let mut app = grouped_app();
+        app.sink_input_list.select(11);
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        assert_eq!(app.sink_input_list.get_selected().unwrap().index, 13);
+    }
+
+    #[test]
+    fn expanded_group_acts_on_single_stream() {
+        let mut app = grouped_app();
+        app.sink_input_list.select(11);
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('e'), &mut app, &mut backend);
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        handle_key_event(Key::Char('m'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::SetSinkInputMute(12, true)]);
+    }
+
+    #[test]
+    fn group_popup_moves_all_streams() {
+        let mut app = grouped_app();
+        app.sink_input_list.select(12);
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('i'), &mut app, &mut backend);
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        handle_key_event(Key::Char('\n'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::MoveSinkInput(11, 2), Call::MoveSinkInput(12, 2)]);
+    }
+}
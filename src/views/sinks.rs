@@ -1,4 +1,4 @@
-use termion::event::Key;
+use termion::event::{Key, MouseButton};
 use ratatui::backend::TermionBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Margin};
 use ratatui::style::{Color, Modifier, Style};
@@ -6,22 +6,41 @@ use ratatui::widgets::{Block, Borders, Gauge, Widget, Paragraph};
 use ratatui::text::Text;
 use ratatui::Terminal;
 
-use pulse::context::Context;
+use crate::backend::Backend;
 use std::sync::atomic;
 use std::sync::{Arc, Mutex};
 
 use pulse::def::SinkState;
 
-use crate::App;
+use crate::{App, AppView};
+use crate::search;
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
+use crate::channels::{ChannelState, VolumeChange, BalanceKind, BALANCE_STEP, has_balance_controls};
 
 #[derive(Default)]
 pub struct ViewData {
+    port_popup_open: bool,
     keybinding_popup_open: bool,
+    keybinding_scroll: usize,
+    port_index_selected: usize,
+    channels: ChannelState,
+    scroll_offset: usize,
 }
 
 impl ViewData {
+    pub fn open_port_popup(&mut self, entry: &crate::SinkEntry) {
+        self.port_popup_open = true;
+        self.port_index_selected = entry.active_port_index.unwrap_or(0);
+    }
+
+    pub fn close_port_popup(&mut self) {
+        self.port_popup_open = false;
+    }
+
     pub fn open_keybinding_popup(&mut self) {
         self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
     }
 
     pub fn close_keybinding_popup(&mut self) {
@@ -30,105 +49,152 @@ impl ViewData {
 }
 
 pub fn entered(app: &mut App) {
+    app.sink_view_data.close_port_popup();
     app.sink_view_data.close_keybinding_popup();
 }
 
 pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
-    let mut constraints = vec![Constraint::Length(3); app.sink_list.len()];
-    constraints.push(Constraint::Min(0));
+    let search = app.search.get(AppView::Sinks);
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(rect);
+    let selected_index = app.sink_list.get_selected().map(|x| x.index);
+    let heights: Vec<u16> = app.sink_list.filtered_values(|x| search::matches(*x, &search))
+        .map(|x| {
+            let balance_height = if has_balance_controls(&x.channel_map) { 1 } else { 0 };
+            app.sink_view_data.channels.entry_height(Some(x.index) == selected_index, x.volume.len()) + balance_height
+        })
+        .collect();
+    let selected_position = app.sink_list.filtered_values(|x| search::matches(*x, &search)).position(|x| Some(x.index) == selected_index);
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.sink_view_data.scroll_offset);
+
+    for (i, sink) in app.sink_list.filtered_values(|x| search::matches(*x, &search)).enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
 
-    for (i, sink) in app.sink_list.values().enumerate() {
         // let volume_ratio = VolumeLinear::from(sink.volume.avg()).0;
         let vol = sink.volume.avg();
         let volume_ratio = vol.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
-        let mut label = format!("{:.0}%", volume_ratio * 100f64);
-        if sink.mute {
-            label += " (muted)";
-        }
+        let label = format!("{:.0}%", volume_ratio * 100f64);
 
-        let title = format!(" {} ", sink.display_name());
+        let mut title = format!(" {}", sink.display_name());
+        if let Some(port) = sink.active_port() {
+            title += &format!("  [{}]", port.display_name());
+        }
+        if app.default_sink_name.as_ref() == Some(&sink.name) {
+            title += "  (default)";
+        }
+        title += " ";
 
         let invalid = sink.mute || sink.state == SinkState::Suspended;
 
-        let color = if sink.index == app.sink_list.get_selected().expect("No selected entry while drawing").index {
-            if invalid { Color::Gray } else { Color::Green }
+        let is_selected = sink.index == app.sink_list.get_selected().expect("No selected entry while drawing").index;
+
+        let color = if is_selected {
+            if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
         } else if invalid {
-            Color::DarkGray
+            app.config.colors.inactive
         } else if sink.state == SinkState::Idle {
-            Color::Red
+            app.config.colors.idle
         } else {
-            Color::Yellow
+            app.config.colors.normal
         };
 
-        Gauge::default()
-            .block(Block::bordered().title(title))
-            .gauge_style(Style::default().fg(color))
-            .ratio(volume_ratio.min(1.0))
-            .label(&label)
-            .render(chunks[i], frame.buffer_mut());
+        let mut gauge_rect = chunk;
+        if has_balance_controls(&sink.channel_map) && gauge_rect.height > 0 {
+            gauge_rect.height -= 1;
+            let slider_rect = Rect::new(gauge_rect.x, gauge_rect.bottom(), gauge_rect.width, 1);
+            crate::draw::draw_balance_sliders(frame.buffer_mut(), slider_rect, &sink.volume, &sink.channel_map, &app.config.colors);
+        }
+
+        let channels_expanded = app.sink_view_data.channels.entry_height(is_selected, sink.volume.len()) > 3;
+        let volume_area = if channels_expanded { None } else { Some(gauge_rect.inner(&Margin::new(1, 1))) };
+        app.click_targets.push((chunk, ClickTarget::Entry { index: sink.index, volume_area }));
+
+        let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: sink.mute };
+        if channels_expanded {
+            crate::draw::draw_channel_gauges(frame.buffer_mut(), gauge_rect, Block::bordered().title(title),
+                &sink.volume, &sink.channel_map, &app.sink_view_data.channels, style);
+        } else {
+            crate::draw::draw_volume_gauge(frame.buffer_mut(), gauge_rect, Some(Block::bordered().title(title)),
+                volume_ratio, label, style);
+        }
+
+        if let Some(peak) = app.meters.source_peak(sink.monitor_source) {
+            crate::draw::draw_peak_meter(frame.buffer_mut(), gauge_rect, peak, style);
+        }
         }
 
+    if app.sink_view_data.port_popup_open {
+        draw_port_popup(frame, rect, app);
+    }
+
     if app.sink_view_data.keybinding_popup_open {
-        draw_keybinding_popup(frame, rect, app);
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::Sinks, &mut app.sink_view_data.keybinding_scroll);
     }
 }
 
-pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
-
-    let keys = vec![
-        ( "F1 through F5", "Change tab"),
-        ( "Tab", "Cycle tabs"),
-        ( "q  crtl-c", "Quit"),
-        ( "?", "Hotkeys"),
-        ( "Esc", "Close popup"),
-        ( "j/down  k/up", "Movement"),
-        ( "^  1 through 0", "Audio level shortcut"),
-        ( "m", "Toggle mute"),
-        ( "h  l", "Volume down / up"),
-        ( "H  L", "Volume down / up (10% steps)"),
-        ( "ctrl-H  ctrl-L", "Volume 0% / 100%"),
-        ( "D", "Unload owner module (remove sink)"),
-    ];
+pub fn draw_port_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
+
+    let focused_sink = match app.sink_list.get_selected() {
+        None => { app.sink_view_data.close_port_popup(); return; },
+        Some(x) => x,
+    };
 
     let rect = rect.inner(&Margin::new(4, 4));
     crate::draw::ClearingWidget::default()
         .render(rect, frame.buffer_mut());
 
-    let block = Block::bordered().title(" Keybindings ");
+    let block = Block::bordered().title(" Change Port ");
     let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
                                    // the block
     block.render(rect, frame.buffer_mut());
 
     let list = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1); keys.len()])
+        .constraints(vec![Constraint::Length(1); focused_sink.ports.len()])
         .split(inner);
 
-    for (j, (key, desc)) in keys.iter().enumerate() {
-        Paragraph::new(Text::raw(format!(" {:^17} {}", key, desc)))
-                .render(list[j], frame.buffer_mut());
+    for (j, port) in focused_sink.ports.iter().enumerate() {
+        let mut style = Style::default();
+        if app.sink_view_data.port_index_selected == j {
+            style = Style::default().fg(app.config.colors.popup_cursor)
+        }
+        if focused_sink.active_port_index == Some(j) {
+            style = Style::default().fg(app.config.colors.popup_current)
+        }
+        app.click_targets.push((list[j], ClickTarget::PopupEntry(j as u32)));
+        let text = match port.availability_label() {
+            ""    => format!(" {} ", port.display_name()),
+            label => format!(" {} ({}) ", port.display_name(), label),
+        };
+        Paragraph::new(Text::raw(text))
+            .style(style)
+            .render(list[j], frame.buffer_mut());
     }
 }
 
-pub fn handle_key_event(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
     if app.sink_view_data.keybinding_popup_open {
-        handle_key_event_keybinding_popup(key, app, context);
+        handle_key_event_keybinding_popup(key, app, backend);
+    } else if app.sink_view_data.port_popup_open {
+        handle_key_event_port_popup(key, app, backend);
     } else {
-        handle_key_event_main(key, app, context);
+        handle_key_event_main(key, app, backend);
     }
 }
 
-pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.action(KeymapSection::Sinks, key) {
+        Some(action) => action,
+        None => return,
+    };
 
-    match key {
-        Key::Char('?') => {
+    match action {
+        Action::Help => {
             app.sink_view_data.open_keybinding_popup();
             app.redraw = true;
             return;
@@ -136,80 +202,122 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
         _ => {}
     }
 
-    if let Some(sink) = app.sink_list.get_selected() {
-        match key {
-            Key::Char('j') | Key::Down => {
-                app.sink_list.select_next();
+    // with a search that matches nothing, the selected entry is not shown
+    let search = app.search.get(AppView::Sinks);
+    if let Some(sink) = app.sink_list.get_selected().filter(|x| search::matches(*x, &search)) {
+        match action {
+            Action::Down => {
+                app.sink_list.filtered_select_next(|x| search::matches(x, &search));
             }
-            Key::Char('k') | Key::Up => {
-                app.sink_list.select_prev();
+            Action::Up => {
+                app.sink_list.filtered_select_prev(|x| search::matches(x, &search));
             }
-            Key::Char('m') => {
-                context.introspect().set_sink_mute_by_index(sink.index, !sink.mute, None);
+            Action::ToggleMute => {
+                backend.set_sink_mute(sink.index, !sink.mute);
             }
-            Key::Char('h') | Key::Left => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+            Action::VolumeDown => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Decrease(app.config.volume_step_small), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Char('l') | Key::Right => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+            Action::VolumeUp => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Increase(app.config.volume_step_small), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Char('H') => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+            Action::VolumeDownBig => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Decrease(app.config.volume_step_big), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Char('L') => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+            Action::VolumeUpBig => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Increase(app.config.volume_step_big), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Ctrl('h') => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.mute(new_vol.len());
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+            Action::VolumeMin => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Set(pulse::volume::Volume::MUTED.0), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Ctrl('l') => {
-                let mut new_vol = sink.volume.clone();
-                new_vol.reset(new_vol.len());
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
-            }
-            Key::Char('^')
-                | Key::Char('1')
-                | Key::Char('2')
-                | Key::Char('3')
-                | Key::Char('4')
-                | Key::Char('5')
-                | Key::Char('6')
-                | Key::Char('7')
-                | Key::Char('8')
-                | Key::Char('9')
-                | Key::Char('0') => {
-
-                let factor = match key {
-                    Key::Char('^') => 0,
-                    Key::Char('1') => 1,
-                    Key::Char('2') => 2,
-                    Key::Char('3') => 3,
-                    Key::Char('4') => 4,
-                    Key::Char('5') => 5,
-                    Key::Char('6') => 6,
-                    Key::Char('7') => 7,
-                    Key::Char('8') => 8,
-                    Key::Char('9') => 9,
-                    Key::Char('0') => 10,
-                    _ => 0,
-                };
+            Action::VolumeNormal => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
+            }
+            Action::ToggleChannels => {
+                app.sink_view_data.channels.toggle_expanded();
+                app.redraw = true;
+            }
+            Action::LockChannels => {
+                app.sink_view_data.channels.toggle_locked();
+                app.redraw = true;
+            }
+            Action::NextChannel => {
+                app.sink_view_data.channels.select_next(sink.volume.len());
+                app.redraw = true;
+            }
+            Action::PrevChannel => {
+                app.sink_view_data.channels.select_prev();
+                app.redraw = true;
+            }
+            Action::BalanceLeft => {
+                if let Some(new_vol) = BalanceKind::Balance.change(&sink.volume, &sink.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::BalanceRight => {
+                if let Some(new_vol) = BalanceKind::Balance.change(&sink.volume, &sink.channel_map, BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::FadeRear => {
+                if let Some(new_vol) = BalanceKind::Fade.change(&sink.volume, &sink.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::FadeFront => {
+                if let Some(new_vol) = BalanceKind::Fade.change(&sink.volume, &sink.channel_map, BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::LfeDown => {
+                if let Some(new_vol) = BalanceKind::Lfe.change(&sink.volume, &sink.channel_map, -BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::LfeUp => {
+                if let Some(new_vol) = BalanceKind::Lfe.change(&sink.volume, &sink.channel_map, BALANCE_STEP) {
+                    backend.set_sink_volume(sink.index, &new_vol);
+                }
+            }
+            Action::ResetBalance => {
                 let mut new_vol = sink.volume.clone();
-                new_vol.set(new_vol.len(), pulse::volume::Volume{0: pulse::volume::Volume::NORMAL.0 / 10 * factor});
-                context.introspect().set_sink_volume_by_index(sink.index, &new_vol, None);
+                for kind in BalanceKind::ALL.iter() {
+                    if let Some(v) = kind.set(&new_vol, &sink.channel_map, 0.0) {
+                        new_vol = v;
+                    }
+                }
+                backend.set_sink_volume(sink.index, &new_vol);
             }
-            Key::Char('D') => {
+            Action::VolumeLevel(factor) => {
+                let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor), app.max_volume);
+                backend.set_sink_volume(sink.index, &new_vol);
+            }
+            Action::ChoosePort => {
+                if !sink.ports.is_empty() {
+                    app.sink_view_data.open_port_popup(sink);
+                    app.redraw = true;
+                }
+            }
+            Action::SetDefault => {
+                backend.set_default_sink(&sink.name);
+            }
+            Action::SetDefaultMoveAll => {
+                backend.set_default_sink(&sink.name);
+                for stream in app.sink_input_list.values() {
+                    if stream.sink_index != sink.index {
+                        backend.move_sink_input(stream.index, sink.index);
+                    }
+                }
+            }
+            Action::UnloadModule => {
                 if let Some(owner_module_id) = sink.owner_module {
-                    context.introspect().unload_module(owner_module_id, |_| {});
+                    backend.unload_module(owner_module_id);
                 }
             }
             _ => {}
@@ -217,12 +325,181 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, context: &Context) {
-    match key {
-        Key::Esc => {
+pub fn handle_key_event_port_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Sinks, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    let sink = match app.sink_list.get_selected() {
+        Some(sink) => sink,
+        None => {
+            app.sink_view_data.close_port_popup();
+            return;
+        }
+    };
+
+    match action {
+        Action::ClosePopup => {
+            app.sink_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        Action::Confirm => {
+            if let Some(port) = sink.ports.get(app.sink_view_data.port_index_selected) {
+                backend.set_sink_port(sink.index, &port.name);
+            }
+            app.sink_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        Action::Down => {
+            if app.sink_view_data.port_index_selected + 1 < sink.ports.len() {
+                app.sink_view_data.port_index_selected += 1;
+                app.redraw = true;
+            }
+        }
+        Action::Up => {
+            if app.sink_view_data.port_index_selected > 0 {
+                app.sink_view_data.port_index_selected -= 1;
+                app.redraw = true;
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Sinks, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
             app.sink_view_data.close_keybinding_popup();
             app.redraw = true;
         }
+        Action::Down => {
+            app.sink_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.sink_view_data.keybinding_scroll = app.sink_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
         _ => {}
     }
 }
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.sink_view_data.keybinding_popup_open {
+        return;
+    }
+
+    if app.sink_view_data.port_popup_open {
+        if let ClickTarget::PopupEntry(index) = target {
+            if let Some(sink) = app.sink_list.get_selected() {
+                if let Some(port) = sink.ports.get(index as usize) {
+                    backend.set_sink_port(sink.index, &port.name);
+                }
+            }
+            app.sink_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        return;
+    }
+
+    if let ClickTarget::Entry { index, volume_area } = target {
+        app.sink_list.select(index);
+
+        let sink = match app.sink_list.get(index) {
+            Some(sink) => sink,
+            None => return,
+        };
+
+        let change = match button {
+            MouseButton::Left => match volume_area {
+                Some(area) if area.contains(position) => VolumeChange::Set(crate::draw::volume_at(area, position.x, app.max_volume)),
+                _ => return,
+            },
+            MouseButton::WheelUp   => VolumeChange::Increase(app.config.volume_step_small),
+            MouseButton::WheelDown => VolumeChange::Decrease(app.config.volume_step_small),
+            _ => return,
+        };
+        let new_vol = app.sink_view_data.channels.change_volume(&sink.volume, change, app.max_volume);
+        backend.set_sink_volume(sink.index, &new_vol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Call, FakeBackend};
+    use crate::test_util::{test_app, test_sink, test_sink_input};
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.sink_input_list.update(10, test_sink_input(10, "music", 1));
+        app.sink_input_list.update(11, test_sink_input(11, "call", 2));
+        return app;
+    }
+
+    #[test]
+    fn volume_and_mute_apply_to_selected_sink() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        handle_key_event(Key::Char('l'), &mut app, &mut backend);
+        handle_key_event(Key::Char('m'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::SetSinkVolume(2, 101), Call::SetSinkMute(2, true)]);
+    }
+
+    #[test]
+    fn set_default_moves_other_streams() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Ctrl('d'), &mut app, &mut backend);
+        assert_eq!(backend.calls, vec![Call::SetDefaultSink(String::from("speakers")), Call::MoveSinkInput(11, 1)]);
+    }
+
+    #[test]
+    fn sorting_keeps_selection_and_order_is_followed() {
+        let mut app = app();
+        app.sorting.cycle(AppView::Sinks);
+        crate::sorting::sort_all(&mut app);
+        assert_eq!(app.sink_list.get_selected().unwrap().index, 1);
+
+        // headset comes before speakers now
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('j'), &mut app, &mut backend);
+        assert_eq!(app.sink_list.get_selected().unwrap().index, 1);
+        handle_key_event(Key::Char('k'), &mut app, &mut backend);
+        assert_eq!(app.sink_list.get_selected().unwrap().index, 2);
+    }
+
+    #[test]
+    fn only_updated_lists_are_sorted_again() {
+        let mut app = app();
+        app.sorting.cycle(AppView::Sinks);
+        crate::sorting::sort_all(&mut app);
+        assert!(!app.sink_list.needs_sort());
+        assert!(!app.sink_input_list.needs_sort());
+
+        app.sink_list.update(3, test_sink(3, "amplifier"));
+        assert!(app.sink_list.needs_sort());
+        crate::sorting::sort_all(&mut app);
+        let order: Vec<u32> = app.sink_list.values().map(|x| x.index).collect();
+        assert_eq!(order, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn keys_are_ignored_while_help_is_open() {
+        let mut app = app();
+        let mut backend = FakeBackend::default();
+        handle_key_event(Key::Char('?'), &mut app, &mut backend);
+        handle_key_event(Key::Char('m'), &mut app, &mut backend);
+        assert!(backend.calls.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use termion::event::Key;
+use termion::event::{Key, MouseButton};
 use ratatui::backend::TermionBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Margin};
 use ratatui::style::{Color, Modifier, Style};
@@ -6,17 +6,26 @@ use ratatui::widgets::{Block, Borders, Gauge, Widget, Paragraph};
 use ratatui::text::Text;
 use ratatui::Terminal;
 
-use pulse::context::Context;
+use crate::backend::Backend;
 use std::sync::atomic;
 use std::sync::{Arc, Mutex};
 
-use crate::App;
+use crate::{App, AppView};
+use crate::search;
+use crate::groups::{self, Groups, StreamRow};
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
+use crate::channels::{ChannelState, VolumeChange};
 
 #[derive(Default)]
 pub struct ViewData {
     source_popup_open: bool,
     keybinding_popup_open: bool,
+    keybinding_scroll: usize,
     source_index_selected: u32,
+    channels: ChannelState,
+    groups: Groups,
+    scroll_offset: usize,
 }
 
 impl ViewData {
@@ -31,6 +40,7 @@ impl ViewData {
 
     pub fn open_keybinding_popup(&mut self) {
         self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
     }
 
     pub fn close_keybinding_popup(&mut self) {
@@ -43,17 +53,28 @@ pub fn entered(app: &mut App) {
     app.source_output_view_data.close_keybinding_popup();
 }
 
-pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
+/// The rows shown: streams matching the search and not recording from a hidden monitor, grouped by
+/// application if enabled.
+fn rows(app: &App) -> Vec<StreamRow> {
+    let search = app.search.get(AppView::SourceOutputs);
+    let source_list = &app.source_list;
+    let hide_monitors = app.hide_monitors;
+    let streams = app.source_output_list.filtered_values(
+        |x| !((x.source_index == 0xffffffff || source_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false)) && hide_monitors) && search::matches(*x, &search)
+    ).map(|x| (x.index, &x.proplist));
+    return app.source_output_view_data.groups.rows(app.group_streams, streams);
+}
 
-    let mut constraints = vec![Constraint::Length(3); app.source_output_list. filtered_len(
-        |x| !((x.source_index == 0xffffffff || app.source_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false)) && app.hide_monitors)
-    )];
-    constraints.push(Constraint::Min(0));
+/// The streams an action on the selected row applies to.
+fn selected_members(app: &App) -> Vec<u32> {
+    let selected = match app.source_output_list.get_selected() {
+        Some(stream) => stream.index,
+        None => return Vec::new(),
+    };
+    return groups::row_of(&rows(app), selected).map(|x| x.members.clone()).unwrap_or_else(|| vec![selected]);
+}
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(rect);
+pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
     if app.hide_monitors {
         // make sure a none-filtered item is selected
@@ -65,15 +86,37 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
         }
     }
 
-    for (i, stream) in app.source_output_list.filtered_values(
-        |x| !((x.source_index == 0xffffffff || app.source_list.get(x.source_index).map(|x| x.is_monitor()).unwrap_or(false)) && app.hide_monitors)
-    ).enumerate() {
+    let search = app.search.get(AppView::SourceOutputs);
+
+    let rows = rows(app);
+    let selected_index = app.source_output_list.get_selected().map(|x| x.index);
+    let heights: Vec<u16> = rows.iter()
+        .map(|row| {
+            let x = app.source_output_list.get(row.members[0]).expect("Stream of a row is not in the list");
+            if row.group.is_some() {
+                return 3;
+            }
+            app.source_output_view_data.channels.entry_height(Some(x.index) == selected_index, x.volume.len())
+        })
+        .collect();
+    let selected_position = selected_index.and_then(|index| rows.iter().position(|x| x.contains(index)));
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.source_output_view_data.scroll_offset);
+
+    for (i, row) in rows.iter().enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+
+        if row.group.is_some() {
+            draw_group(frame, chunk, row, Some(i) == selected_position, app);
+            continue;
+        }
+        let stream = app.source_output_list.get(row.members[0]).expect("Stream of a row is not in the list");
+
         let vol = stream.volume.avg();
         let volume_ratio = vol.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
-        let mut label = format!("{:.0}%", volume_ratio * 100f64);
-        if stream.mute {
-            label += " (muted)";
-        }
+        let label = format!("{:.0}%", volume_ratio * 100f64);
 
         let display_name = stream.display_name();
         let source_name = app.source_list.get(stream.source_index).map(|s| s.display_name()).unwrap_or(String::from("?"));
@@ -81,20 +124,32 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
         let invalid = stream.mute || !stream.has_volume || stream.corked;
 
-        let color = if stream.index == app.source_output_list.get_selected().expect("No selected entry while drawing").index {
-            if invalid { Color::Gray } else { Color::Green }
+        let is_selected = stream.index == app.source_output_list.get_selected().expect("No selected entry while drawing").index;
+
+        let color = if is_selected {
+            if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
         } else if invalid {
-            Color::DarkGray
+            app.config.colors.inactive
         } else {
-            Color::Yellow
+            app.config.colors.normal
         };
 
-        Gauge::default()
-            .block(Block::bordered().title(name))
-            .gauge_style(Style::default().fg(color))
-            .ratio(volume_ratio.min(1.0))
-            .label(&label)
-            .render(chunks[i], frame.buffer_mut());
+        let channels_expanded = app.source_output_view_data.channels.entry_height(is_selected, stream.volume.len()) > 3;
+        let volume_area = if channels_expanded { None } else { Some(chunk.inner(&Margin::new(1, 1))) };
+        app.click_targets.push((chunk, ClickTarget::Entry { index: stream.index, volume_area }));
+
+        let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: stream.mute };
+        if channels_expanded {
+            crate::draw::draw_channel_gauges(frame.buffer_mut(), chunk, Block::bordered().title(name),
+                &stream.volume, &stream.channel_map, &app.source_output_view_data.channels, style);
+        } else {
+            crate::draw::draw_volume_gauge(frame.buffer_mut(), chunk, Some(Block::bordered().title(name)),
+                volume_ratio, label, style);
+        }
+
+        if let Some(peak) = app.meters.source_peak(stream.source_index) {
+            crate::draw::draw_peak_meter(frame.buffer_mut(), chunk, peak, style);
+        }
         }
 
     if app.source_output_view_data.source_popup_open {
@@ -102,7 +157,51 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
     }
 
     if app.source_output_view_data.keybinding_popup_open {
-        draw_keybinding_popup(frame, rect, app);
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::SourceOutputs, &mut app.source_output_view_data.keybinding_scroll);
+    }
+}
+
+/// A collapsed group, with the average volume of its streams.
+fn draw_group(frame: &mut ratatui::terminal::Frame, chunk: Rect, row: &StreamRow, is_selected: bool, app: &mut App) {
+
+    let stream_list = &app.source_output_list;
+    let streams: Vec<&crate::SourceOutputEntry> = row.members.iter().filter_map(|x| stream_list.get(*x)).collect();
+    let group = row.group.as_deref().unwrap_or("?");
+
+    let vol = streams.iter().map(|x| x.volume.avg().0 as f64).sum::<f64>() / streams.len() as f64;
+    let volume_ratio = vol / pulse::volume::Volume::NORMAL.0 as f64;
+    let label = format!("{:.0}%", volume_ratio * 100f64);
+
+    let mut sources: Vec<u32> = streams.iter().map(|x| x.source_index).collect();
+    sources.sort();
+    sources.dedup();
+    let source_name = match sources[..] {
+        [source_index] => app.source_list.get(source_index).map(|s| s.display_name()).unwrap_or(String::from("?")),
+        _ => String::from("several sources"),
+    };
+    let name = format!(" {} ({} streams)  ->  {} ", group, streams.len(), source_name);
+
+    let invalid = streams.iter().all(|x| x.mute || !x.has_volume || x.corked);
+
+    let color = if is_selected {
+        if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
+    } else if invalid {
+        app.config.colors.inactive
+    } else {
+        app.config.colors.normal
+    };
+
+    let volume_area = Some(chunk.inner(&Margin::new(1, 1)));
+    app.click_targets.push((chunk, ClickTarget::Entry { index: row.members[0], volume_area }));
+
+    let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: streams.iter().all(|x| x.mute) };
+    crate::draw::draw_volume_gauge(frame.buffer_mut(), chunk, Some(Block::bordered().title(name)),
+        volume_ratio, label, style);
+
+    let meters = &app.meters;
+    let peak = streams.iter().filter_map(|x| meters.source_peak(x.source_index)).fold(None, |a: Option<f32>, b| Some(a.map_or(b, |a| a.max(b))));
+    if let Some(peak) = peak {
+        crate::draw::draw_peak_meter(frame.buffer_mut(), chunk, peak, style);
     }
 }
 
@@ -130,83 +229,52 @@ pub fn draw_source_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app:
     for (j, source) in app.source_list.filtered_values(|source| !source.is_monitor()).enumerate() {
         let mut style = Style::default();
         if app.source_output_view_data.source_index_selected == source.index {
-            style = Style::default().fg(Color::Red)
+            style = Style::default().fg(app.config.colors.popup_cursor)
         }
         if focused_stream.source_index == source.index {
-            style = Style::default().fg(Color::Green)
+            style = Style::default().fg(app.config.colors.popup_current)
         }
+        app.click_targets.push((list[j], ClickTarget::PopupEntry(source.index)));
         Paragraph::new(Text::raw(format!(" {} ", source.display_name())))
             .style(style)
             .render(list[j], frame.buffer_mut());
         }
 }
 
-pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
-
-    let keys = vec![
-        ( "F1 through F5", "Change tab"),
-        ( "Tab", "Cycle tabs"),
-        ( "q  crtl-c", "Quit"),
-        ( "?", "Hotkeys"),
-        ( "Esc", "Close popup"),
-        ( "j/down  k/up", "Movement"),
-        ( "^  1 through 0", "Audio level shortcut"),
-        ( "m", "Toggle mute"),
-        ( "h  l", "Volume down / up"),
-        ( "H  L", "Volume down / up (10% steps)"),
-        ( "ctrl-H  ctrl-L", "Volume 0% / 100%"),
-        ( "i  return", "Choose source for selected stream"),
-        ( "K", "Kill stream"),
-        ( "ctrl-k", "Kill all non-running streams"),
-    ];
-
-    let rect = rect.inner(&Margin::new(4, 4));
-    crate::draw::ClearingWidget::default()
-        .render(rect, frame.buffer_mut());
-
-    let block = Block::bordered().title(" Keybindings ");
-    let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
-                                   // the block
-    block.render(rect, frame.buffer_mut());
-
-    let list = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1); keys.len()])
-        .split(inner);
-
-    for (j, (key, desc)) in keys.iter().enumerate() {
-        Paragraph::new(Text::raw(format!(" {:^17} {}", key, desc)))
-                .render(list[j], frame.buffer_mut());
-    }
-}
-
-pub fn handle_key_event(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
     if app.source_output_view_data.keybinding_popup_open {
-        handle_key_event_keybinding_popup(key, app, context);
+        handle_key_event_keybinding_popup(key, app, backend);
     } else if app.source_output_view_data.source_popup_open {
-        handle_key_event_source_popup(key, app, context);
+        handle_key_event_source_popup(key, app, backend);
     } else {
-        handle_key_event_main(key, app, context);
+        handle_key_event_main(key, app, backend);
     }
 }
 
-pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.action(KeymapSection::SourceOutputs, key) {
+        Some(action) => action,
+        None => return,
+    };
 
     let source_list = &app.source_list; // XXX
     let hide_monitors = app.hide_monitors;
-    let filter = |source_output: &crate::SourceOutputEntry| !(source_list.get(source_output.source_index).expect("SourceOutputEntry.source_index not in list").is_monitor() && hide_monitors);
+    // a source output can show up before the source it records from
+    let filter = |source_output: &crate::SourceOutputEntry| !(source_list.get(source_output.source_index).map(|x| x.is_monitor()).unwrap_or(false) && hide_monitors);
+    let search = app.search.get(AppView::SourceOutputs);
 
-    match key {
-        Key::Ctrl('k') => {
-            for stream in app.source_output_list.values() {
+    match action {
+        Action::KillInactive => {
+            for stream in app.source_output_list.filtered_values(|x| search::matches(*x, &search)) {
                 if stream.corked {
-                    context.introspect().kill_source_output(stream.index, |_| {});
+                    backend.kill_source_output(stream.index);
                 }
             }
             return;
         }
-        Key::Char('?') => {
+        Action::Help => {
             app.source_output_view_data.open_keybinding_popup();
             app.redraw = true;
             return;
@@ -214,98 +282,94 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
         _ => {}
     }
 
-    if let Some(stream) = app.source_output_list.get_selected() {
-        match key {
-            Key::Char('j') | Key::Down => {
-                if app.hide_monitors {
-                    app.source_output_list.filtered_select_next(filter);
-                } else {
-                    app.source_output_list.select_next();
+    // with a search that matches nothing, the selected entry is not shown
+    if let Some(stream) = app.source_output_list.get_selected().filter(|x| search::matches(*x, &search)) {
+        let rows = rows(app);
+        let in_group = groups::row_of(&rows, stream.index).map(|x| x.group.is_some()).unwrap_or(false);
+        match action {
+            Action::Down => {
+                if let Some(index) = groups::next_row(&rows, stream.index, true) {
+                    app.source_output_list.select(index);
                 }
             }
-            Key::Char('k') | Key::Up => {
-                if app.hide_monitors {
-                    app.source_output_list.filtered_select_prev(filter);
-                } else {
-                    app.source_output_list.select_prev();
+            Action::Up => {
+                if let Some(index) = groups::next_row(&rows, stream.index, false) {
+                    app.source_output_list.select(index);
+                }
+            }
+            Action::ToggleGroups => {
+                app.group_streams = !app.group_streams;
+                app.redraw = true;
+            }
+            Action::ExpandGroup => {
+                if let Some(key) = groups::group_key(&stream.proplist) {
+                    app.source_output_view_data.groups.toggle_expanded(key);
+                    app.redraw = true;
                 }
             }
-            Key::Char('m') => {
+            _ if in_group => {
+                let members = selected_members(app);
+                handle_key_event_group(action, &members, app, backend);
+            }
+            Action::ToggleMute => {
                 if app.hide_monitors && !filter(stream) { return; }
-                context.introspect().set_source_output_mute(stream.index, !stream.mute, None);
+                backend.set_source_output_mute(stream.index, !stream.mute);
             }
-            Key::Char('K') => {
+            Action::Kill => {
                 if app.hide_monitors && !filter(stream) { return; }
-                context.introspect().kill_source_output(stream.index, |_| {});
+                backend.kill_source_output(stream.index);
             }
-            Key::Char('h') | Key::Left => {
+            Action::VolumeDown => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Decrease(app.config.volume_step_small), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Char('l') | Key::Right => {
+            Action::VolumeUp => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Increase(app.config.volume_step_small), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Char('H') => {
+            Action::VolumeDownBig => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Decrease(app.config.volume_step_big), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Char('L') => {
+            Action::VolumeUpBig => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Increase(app.config.volume_step_big), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Ctrl('h') => {
+            Action::VolumeMin => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.mute(new_vol.len());
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::MUTED.0), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Ctrl('l') => {
+            Action::VolumeNormal => {
                 if app.hide_monitors && !filter(stream) { return; }
-                let mut new_vol = stream.volume.clone();
-                new_vol.reset(new_vol.len());
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
+            }
+            Action::ToggleChannels => {
+                app.source_output_view_data.channels.toggle_expanded();
+                app.redraw = true;
+            }
+            Action::LockChannels => {
+                app.source_output_view_data.channels.toggle_locked();
+                app.redraw = true;
+            }
+            Action::NextChannel => {
+                app.source_output_view_data.channels.select_next(stream.volume.len());
+                app.redraw = true;
             }
-            Key::Char('^')
-                | Key::Char('1')
-                | Key::Char('2')
-                | Key::Char('3')
-                | Key::Char('4')
-                | Key::Char('5')
-                | Key::Char('6')
-                | Key::Char('7')
-                | Key::Char('8')
-                | Key::Char('9')
-                | Key::Char('0') => {
-
-                let factor = match key {
-                    Key::Char('^') => 0,
-                    Key::Char('1') => 1,
-                    Key::Char('2') => 2,
-                    Key::Char('3') => 3,
-                    Key::Char('4') => 4,
-                    Key::Char('5') => 5,
-                    Key::Char('6') => 6,
-                    Key::Char('7') => 7,
-                    Key::Char('8') => 8,
-                    Key::Char('9') => 9,
-                    Key::Char('0') => 10,
-                    _ => 0,
-                };
-                let mut new_vol = stream.volume.clone();
-                new_vol.set(new_vol.len(), pulse::volume::Volume{0: pulse::volume::Volume::NORMAL.0 / 10 * factor});
-                context.introspect().set_source_output_volume(stream.index, &new_vol, None);
+            Action::PrevChannel => {
+                app.source_output_view_data.channels.select_prev();
+                app.redraw = true;
+            }
+            Action::VolumeLevel(factor) => {
+                let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor), app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
             }
-            Key::Char('\n') |
-            Key::Char('i') => {
+            Action::ChooseDevice => {
                 app.source_output_view_data.open_source_popup(stream);
                 app.redraw = true;
             }
@@ -314,7 +378,56 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_source_popup(key: Key, app: &mut App, context: &Context) {
+/// Actions on a collapsed group apply to all of its streams, a volume change keeps the level of
+/// the streams relative to each other.
+fn handle_key_event_group(action: Action, members: &[u32], app: &mut App, backend: &mut dyn Backend) {
+
+    let stream_list = &app.source_output_list;
+    let streams: Vec<&crate::SourceOutputEntry> = members.iter().filter_map(|x| stream_list.get(*x)).collect();
+
+    let change = match action {
+        Action::ToggleMute => {
+            let mute = !streams.iter().all(|x| x.mute);
+            for stream in streams.iter() {
+                backend.set_source_output_mute(stream.index, mute);
+            }
+            return;
+        }
+        Action::Kill => {
+            for stream in streams.iter() {
+                backend.kill_source_output(stream.index);
+            }
+            return;
+        }
+        Action::ChooseDevice => {
+            app.source_output_view_data.open_source_popup(streams[0]);
+            app.redraw = true;
+            return;
+        }
+        Action::VolumeDown          => VolumeChange::Decrease(app.config.volume_step_small),
+        Action::VolumeUp            => VolumeChange::Increase(app.config.volume_step_small),
+        Action::VolumeDownBig       => VolumeChange::Decrease(app.config.volume_step_big),
+        Action::VolumeUpBig         => VolumeChange::Increase(app.config.volume_step_big),
+        Action::VolumeMin           => VolumeChange::Set(pulse::volume::Volume::MUTED.0),
+        Action::VolumeNormal        => VolumeChange::Set(pulse::volume::Volume::NORMAL.0),
+        Action::VolumeLevel(factor) => VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor),
+        _ => return,
+    };
+
+    // the channels of a group are never expanded, so all channels change together
+    let channels = ChannelState::default();
+    for stream in streams.iter() {
+        let new_vol = channels.change_volume(&stream.volume, change, app.max_volume);
+        backend.set_source_output_volume(stream.index, &new_vol);
+    }
+}
+
+pub fn handle_key_event_source_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::SourceOutputs, key) {
+        Some(action) => action,
+        None => return,
+    };
 
     let stream = match app.source_output_list.get_selected() {
         Some(stream) => stream,
@@ -324,17 +437,19 @@ pub fn handle_key_event_source_popup(key: Key, app: &mut App, context: &Context)
         }
     };
 
-    match key {
-        Key::Esc => {
+    match action {
+        Action::ClosePopup => {
             app.source_output_view_data.close_source_popup();
             app.redraw = true;
         }
-        Key::Char('\n') => {
-            context.introspect().move_source_output_by_index(stream.index, app.source_output_view_data.source_index_selected, None);
+        Action::Confirm => {
+            for index in selected_members(app) {
+                backend.move_source_output(index, app.source_output_view_data.source_index_selected);
+            }
             app.source_output_view_data.close_source_popup();
             app.redraw = true;
         }
-        Key::Char('j') | Key::Down => {
+        Action::Down => {
             if let Some(k) = app.source_list.filtered_next_key(
                     app.source_output_view_data.source_index_selected,
                     |source| !source.is_monitor()) {
@@ -342,7 +457,7 @@ pub fn handle_key_event_source_popup(key: Key, app: &mut App, context: &Context)
                 app.redraw = true;
             }
         }
-        Key::Char('k') | Key::Up => {
+        Action::Up => {
             if let Some(k) = app.source_list.filtered_prev_key(
                     app.source_output_view_data.source_index_selected,
                     |source| !source.is_monitor()) {
@@ -354,12 +469,74 @@ pub fn handle_key_event_source_popup(key: Key, app: &mut App, context: &Context)
     }
 }
 
-pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, context: &Context) {
-    match key {
-        Key::Esc => {
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::SourceOutputs, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
             app.source_output_view_data.close_keybinding_popup();
             app.redraw = true;
         }
+        Action::Down => {
+            app.source_output_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.source_output_view_data.keybinding_scroll = app.source_output_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
         _ => {}
     }
 }
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.source_output_view_data.keybinding_popup_open {
+        return;
+    }
+
+    if app.source_output_view_data.source_popup_open {
+        if let ClickTarget::PopupEntry(index) = target {
+            for stream in selected_members(app) {
+                backend.move_source_output(stream, index);
+            }
+            app.source_output_view_data.close_source_popup();
+            app.redraw = true;
+        }
+        return;
+    }
+
+    if let ClickTarget::Entry { index, volume_area } = target {
+        app.source_output_list.select(index);
+
+        let stream = match app.source_output_list.get(index) {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        let change = match button {
+            MouseButton::Left => match volume_area {
+                Some(area) if area.contains(position) => VolumeChange::Set(crate::draw::volume_at(area, position.x, app.max_volume)),
+                _ => return,
+            },
+            MouseButton::WheelUp   => VolumeChange::Increase(app.config.volume_step_small),
+            MouseButton::WheelDown => VolumeChange::Decrease(app.config.volume_step_small),
+            _ => return,
+        };
+        let members = selected_members(app);
+        if members.len() > 1 {
+            let stream_list = &app.source_output_list;
+            for stream in members.iter().filter_map(|x| stream_list.get(*x)) {
+                let new_vol = ChannelState::default().change_volume(&stream.volume, change, app.max_volume);
+                backend.set_source_output_volume(stream.index, &new_vol);
+            }
+            return;
+        }
+        let new_vol = app.source_output_view_data.channels.change_volume(&stream.volume, change, app.max_volume);
+        backend.set_source_output_volume(stream.index, &new_vol);
+    }
+}
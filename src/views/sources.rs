@@ -1,4 +1,4 @@
-use termion::event::Key;
+use termion::event::{Key, MouseButton};
 use ratatui::backend::TermionBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Margin};
 use ratatui::style::{Color, Modifier, Style};
@@ -6,22 +6,41 @@ use ratatui::widgets::{Block, Borders, Gauge, Widget, Paragraph};
 use ratatui::text::Text;
 use ratatui::Terminal;
 
-use pulse::context::Context;
+use crate::backend::Backend;
 use std::sync::atomic;
 use std::sync::{Arc, Mutex};
 
 use pulse::def::SourceState;
 
-use crate::App;
+use crate::{App, AppView};
+use crate::search;
+use crate::draw::ClickTarget;
+use crate::config::{Action, KeymapSection};
+use crate::channels::{ChannelState, VolumeChange};
 
 #[derive(Default)]
 pub struct ViewData {
+    port_popup_open: bool,
     keybinding_popup_open: bool,
+    keybinding_scroll: usize,
+    port_index_selected: usize,
+    channels: ChannelState,
+    scroll_offset: usize,
 }
 
 impl ViewData {
+    pub fn open_port_popup(&mut self, entry: &crate::SourceEntry) {
+        self.port_popup_open = true;
+        self.port_index_selected = entry.active_port_index.unwrap_or(0);
+    }
+
+    pub fn close_port_popup(&mut self) {
+        self.port_popup_open = false;
+    }
+
     pub fn open_keybinding_popup(&mut self) {
         self.keybinding_popup_open = true;
+        self.keybinding_scroll = 0;
     }
 
     pub fn close_keybinding_popup(&mut self) {
@@ -30,19 +49,12 @@ impl ViewData {
 }
 
 pub fn entered(app: &mut App) {
+    app.source_view_data.close_port_popup();
     app.source_view_data.close_keybinding_popup();
 }
 
 pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
 
-    let mut constraints = vec![Constraint::Length(3); app.source_list.filtered_len(|x| !(x.is_monitor() && app.hide_monitors))];
-    constraints.push(Constraint::Min(0));
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(rect);
-
     if app.hide_monitors {
         // make sure a none-filtered item is selected
         if let Some(item) = app.source_list.get_selected() {
@@ -52,91 +64,136 @@ pub fn draw(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
         }
     }
 
-    for (i, source) in app.source_list.filtered_values(|x| !(x.is_monitor() && app.hide_monitors)).enumerate() {
+    let search = app.search.get(AppView::Sources);
+
+    let selected_index = app.source_list.get_selected().map(|x| x.index);
+    let heights: Vec<u16> = app.source_list.filtered_values(|x| !(x.is_monitor() && app.hide_monitors) && search::matches(*x, &search))
+        .map(|x| app.source_view_data.channels.entry_height(Some(x.index) == selected_index, x.volume.len()))
+        .collect();
+    let selected_position = app.source_list.filtered_values(|x| !(x.is_monitor() && app.hide_monitors) && search::matches(*x, &search)).position(|x| Some(x.index) == selected_index);
+    let chunks = crate::draw::layout_list(frame.buffer_mut(), rect, &heights, selected_position, &mut app.source_view_data.scroll_offset);
+
+    let hide_monitors = app.hide_monitors;
+    for (i, source) in app.source_list.filtered_values(|x| !(x.is_monitor() && hide_monitors) && search::matches(*x, &search)).enumerate() {
+        let chunk = match chunks[i] {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+
         let vol = source.volume.avg();
         let volume_ratio = vol.0 as f64 / pulse::volume::Volume::NORMAL.0 as f64;
-        let mut label = format!("{:.0}%", volume_ratio * 100f64);
-        if source.mute {
-            label += " (muted)";
-        }
+        let label = format!("{:.0}%", volume_ratio * 100f64);
 
-        let title = format!(" {} ", source.display_name());
+        let mut title = format!(" {}", source.display_name());
+        if let Some(port) = source.active_port() {
+            title += &format!("  [{}]", port.display_name());
+        }
+        if app.default_source_name.as_ref() == Some(&source.name) {
+            title += "  (default)";
+        }
+        title += " ";
 
         let invalid = source.mute || source.state == SourceState::Suspended;
 
-        let color = if source.index == app.source_list.get_selected().expect("No selected entry while drawing").index {
-            if invalid { Color::Gray } else { Color::Green }
+        let is_selected = source.index == app.source_list.get_selected().expect("No selected entry while drawing").index;
+
+        let color = if is_selected {
+            if invalid { app.config.colors.selected_inactive } else { app.config.colors.selected }
         } else if invalid {
-            Color::DarkGray
+            app.config.colors.inactive
         } else if source.state == SourceState::Idle {
-            Color::Red
+            app.config.colors.idle
         } else {
-            Color::Yellow
+            app.config.colors.normal
         };
 
-        Gauge::default()
-            .block(Block::bordered().title(title))
-            .gauge_style(Style::default().fg(color))
-            .ratio(volume_ratio.min(1.0))
-            .label(&label)
-            .render(chunks[i], frame.buffer_mut());
+        let channels_expanded = app.source_view_data.channels.entry_height(is_selected, source.volume.len()) > 3;
+        let volume_area = if channels_expanded { None } else { Some(chunk.inner(&Margin::new(1, 1))) };
+        app.click_targets.push((chunk, ClickTarget::Entry { index: source.index, volume_area }));
+
+        let style = crate::draw::GaugeStyle { max_volume: app.max_volume, color: color, colors: &app.config.colors, muted: source.mute };
+        if channels_expanded {
+            crate::draw::draw_channel_gauges(frame.buffer_mut(), chunk, Block::bordered().title(title),
+                &source.volume, &source.channel_map, &app.source_view_data.channels, style);
+        } else {
+            crate::draw::draw_volume_gauge(frame.buffer_mut(), chunk, Some(Block::bordered().title(title)),
+                volume_ratio, label, style);
         }
 
+        if let Some(peak) = app.meters.source_peak(source.index) {
+            crate::draw::draw_peak_meter(frame.buffer_mut(), chunk, peak, style);
+        }
+        }
+
+    if app.source_view_data.port_popup_open {
+        draw_port_popup(frame, rect, app);
+    }
+
     if app.source_view_data.keybinding_popup_open {
-        draw_keybinding_popup(frame, rect, app);
+        crate::draw::draw_keybinding_popup(frame, rect, &app.config.keymap, KeymapSection::Sources, &mut app.source_view_data.keybinding_scroll);
     }
 }
 
-pub fn draw_keybinding_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
-
-    let keys = vec![
-        ( "F1 through F5", "Change tab"),
-        ( "Tab", "Cycle tabs"),
-        ( "q  crtl-c", "Quit"),
-        ( "?", "Hotkeys"),
-        ( "Esc", "Close popup"),
-        ( "j/down  k/up", "Movement"),
-        ( "^  1 through 0", "Audio level shortcut"),
-        ( "m", "Toggle mute"),
-        ( "h  l", "Volume down / up"),
-        ( "H  L", "Volume down / up (10% steps)"),
-        ( "ctrl-H  ctrl-L", "Volume 0% / 100%"),
-        ( "D", "Unload owner module (remove source)"),
-    ];
+pub fn draw_port_popup(frame: &mut ratatui::terminal::Frame, rect: Rect, app: &mut App) {
+
+    let focused_source = match app.source_list.get_selected() {
+        None => { app.source_view_data.close_port_popup(); return; },
+        Some(x) => x,
+    };
 
     let rect = rect.inner(&Margin::new(4, 4));
     crate::draw::ClearingWidget::default()
         .render(rect, frame.buffer_mut());
 
-    let block = Block::bordered().title(" Keybindings ");
+    let block = Block::bordered().title(" Change Port ");
     let inner = block.inner(rect); // save inner rectangle size for list, as block.render consumes
                                    // the block
     block.render(rect, frame.buffer_mut());
 
     let list = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1); keys.len()])
+        .constraints(vec![Constraint::Length(1); focused_source.ports.len()])
         .split(inner);
 
-    for (j, (key, desc)) in keys.iter().enumerate() {
-        Paragraph::new(Text::raw(format!(" {:^17} {}", key, desc)))
-                .render(list[j], frame.buffer_mut());
+    for (j, port) in focused_source.ports.iter().enumerate() {
+        let mut style = Style::default();
+        if app.source_view_data.port_index_selected == j {
+            style = Style::default().fg(app.config.colors.popup_cursor)
+        }
+        if focused_source.active_port_index == Some(j) {
+            style = Style::default().fg(app.config.colors.popup_current)
+        }
+        app.click_targets.push((list[j], ClickTarget::PopupEntry(j as u32)));
+        let text = match port.availability_label() {
+            ""    => format!(" {} ", port.display_name()),
+            label => format!(" {} ({}) ", port.display_name(), label),
+        };
+        Paragraph::new(Text::raw(text))
+            .style(style)
+            .render(list[j], frame.buffer_mut());
     }
 }
 
-pub fn handle_key_event(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
     if app.source_view_data.keybinding_popup_open {
-        handle_key_event_keybinding_popup(key, app, context);
+        handle_key_event_keybinding_popup(key, app, backend);
+    } else if app.source_view_data.port_popup_open {
+        handle_key_event_port_popup(key, app, backend);
     } else {
-        handle_key_event_main(key, app, context);
+        handle_key_event_main(key, app, backend);
     }
 }
 
-pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
+pub fn handle_key_event_main(key: Key, app: &mut App, backend: &mut dyn Backend) {
 
-    match key {
-        Key::Char('?') => {
+    let action = match app.config.keymap.action(KeymapSection::Sources, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::Help => {
             app.source_view_data.open_keybinding_popup();
             app.redraw = true;
             return;
@@ -144,96 +201,92 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
         _ => {}
     }
 
-    if let Some(source) = app.source_list.get_selected() {
-        match key {
-            Key::Char('j') | Key::Down => {
-                if app.hide_monitors {
-                    app.source_list.filtered_select_next(|x| !x.is_monitor());
-                } else {
-                    app.source_list.select_next();
-                }
+    // with a search that matches nothing, the selected entry is not shown
+    let search = app.search.get(AppView::Sources);
+    let hide_monitors = app.hide_monitors;
+    if let Some(source) = app.source_list.get_selected().filter(|x| search::matches(*x, &search)) {
+        match action {
+            Action::Down => {
+                app.source_list.filtered_select_next(|x| !(x.is_monitor() && hide_monitors) && search::matches(x, &search));
             }
-            Key::Char('k') | Key::Up => {
-                if app.hide_monitors {
-                    app.source_list.filtered_select_prev(|x| !x.is_monitor());
-                } else {
-                    app.source_list.select_prev();
-                }
+            Action::Up => {
+                app.source_list.filtered_select_prev(|x| !(x.is_monitor() && hide_monitors) && search::matches(x, &search));
             }
-            Key::Char('m') => {
+            Action::ToggleMute => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                context.introspect().set_source_mute_by_index(source.index, !source.mute, None);
+                backend.set_source_mute(source.index, !source.mute);
             }
-            Key::Char('h') | Key::Left => {
+            Action::VolumeDown => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Decrease(app.config.volume_step_small), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
             }
-            Key::Char('l') | Key::Right => {
+            Action::VolumeUp => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_SMALL});
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Increase(app.config.volume_step_small), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
             }
-            Key::Char('H') => {
+            Action::VolumeDownBig => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.decrease(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Decrease(app.config.volume_step_big), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
             }
-            Key::Char('L') => {
+            Action::VolumeUpBig => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.increase(pulse::volume::Volume{0: crate::VOLUME_STEP_BIG});
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Increase(app.config.volume_step_big), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
             }
-            Key::Ctrl('h') => {
+            Action::VolumeMin => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.mute(new_vol.len());
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Set(pulse::volume::Volume::MUTED.0), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
             }
-            Key::Ctrl('l') => {
+            Action::VolumeNormal => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let mut new_vol = source.volume.clone();
-                new_vol.reset(new_vol.len());
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
-            }
-            Key::Char('^')
-                | Key::Char('1')
-                | Key::Char('2')
-                | Key::Char('3')
-                | Key::Char('4')
-                | Key::Char('5')
-                | Key::Char('6')
-                | Key::Char('7')
-                | Key::Char('8')
-                | Key::Char('9')
-                | Key::Char('0') => {
-
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
+            }
+            Action::ToggleChannels => {
+                app.source_view_data.channels.toggle_expanded();
+                app.redraw = true;
+            }
+            Action::LockChannels => {
+                app.source_view_data.channels.toggle_locked();
+                app.redraw = true;
+            }
+            Action::NextChannel => {
+                app.source_view_data.channels.select_next(source.volume.len());
+                app.redraw = true;
+            }
+            Action::PrevChannel => {
+                app.source_view_data.channels.select_prev();
+                app.redraw = true;
+            }
+            Action::VolumeLevel(factor) => {
                 if app.hide_monitors && source.is_monitor() { return; }
-                let factor = match key {
-                    Key::Char('^') => 0,
-                    Key::Char('1') => 1,
-                    Key::Char('2') => 2,
-                    Key::Char('3') => 3,
-                    Key::Char('4') => 4,
-                    Key::Char('5') => 5,
-                    Key::Char('6') => 6,
-                    Key::Char('7') => 7,
-                    Key::Char('8') => 8,
-                    Key::Char('9') => 9,
-                    Key::Char('0') => 10,
-                    _ => 0,
-                };
-                let mut new_vol = source.volume.clone();
-                new_vol.set(new_vol.len(), pulse::volume::Volume{0: pulse::volume::Volume::NORMAL.0 / 10 * factor});
-                context.introspect().set_source_volume_by_index(source.index, &new_vol, None);
-            }
-            Key::Char('D') => {
+                let new_vol = app.source_view_data.channels.change_volume(&source.volume, VolumeChange::Set(pulse::volume::Volume::NORMAL.0 / 10 * factor), app.max_volume);
+                backend.set_source_volume(source.index, &new_vol);
+            }
+            Action::ChoosePort => {
+                if !source.ports.is_empty() {
+                    app.source_view_data.open_port_popup(source);
+                    app.redraw = true;
+                }
+            }
+            Action::SetDefault => {
+                backend.set_default_source(&source.name);
+            }
+            Action::SetDefaultMoveAll => {
+                backend.set_default_source(&source.name);
+                for stream in app.source_output_list.values() {
+                    if stream.source_index != source.index {
+                        backend.move_source_output(stream.index, source.index);
+                    }
+                }
+            }
+            Action::UnloadModule => {
                 if let Some(owner_module_id) = source.owner_module {
-                    context.introspect().unload_module(owner_module_id, |_| {});
+                    backend.unload_module(owner_module_id);
                 }
             }
             _ => {}
@@ -241,12 +294,110 @@ pub fn handle_key_event_main(key: Key, app: &mut App, context: &Context) {
     }
 }
 
-pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, context: &Context) {
-    match key {
-        Key::Esc => {
+pub fn handle_key_event_port_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Sources, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    let source = match app.source_list.get_selected() {
+        Some(source) => source,
+        None => {
+            app.source_view_data.close_port_popup();
+            return;
+        }
+    };
+
+    match action {
+        Action::ClosePopup => {
+            app.source_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        Action::Confirm => {
+            if let Some(port) = source.ports.get(app.source_view_data.port_index_selected) {
+                backend.set_source_port(source.index, &port.name);
+            }
+            app.source_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        Action::Down => {
+            if app.source_view_data.port_index_selected + 1 < source.ports.len() {
+                app.source_view_data.port_index_selected += 1;
+                app.redraw = true;
+            }
+        }
+        Action::Up => {
+            if app.source_view_data.port_index_selected > 0 {
+                app.source_view_data.port_index_selected -= 1;
+                app.redraw = true;
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_key_event_keybinding_popup(key: Key, app: &mut App, backend: &mut dyn Backend) {
+
+    let action = match app.config.keymap.popup_action(KeymapSection::Sources, key) {
+        Some(action) => action,
+        None => return,
+    };
+
+    match action {
+        Action::ClosePopup => {
             app.source_view_data.close_keybinding_popup();
             app.redraw = true;
         }
+        Action::Down => {
+            app.source_view_data.keybinding_scroll += 1;
+            app.redraw = true;
+        }
+        Action::Up => {
+            app.source_view_data.keybinding_scroll = app.source_view_data.keybinding_scroll.saturating_sub(1);
+            app.redraw = true;
+        }
         _ => {}
     }
 }
+
+pub fn handle_mouse_event(target: ClickTarget, button: MouseButton, position: ratatui::layout::Position, app: &mut App, backend: &mut dyn Backend) {
+
+    if app.source_view_data.keybinding_popup_open {
+        return;
+    }
+
+    if app.source_view_data.port_popup_open {
+        if let ClickTarget::PopupEntry(index) = target {
+            if let Some(source) = app.source_list.get_selected() {
+                if let Some(port) = source.ports.get(index as usize) {
+                    backend.set_source_port(source.index, &port.name);
+                }
+            }
+            app.source_view_data.close_port_popup();
+            app.redraw = true;
+        }
+        return;
+    }
+
+    if let ClickTarget::Entry { index, volume_area } = target {
+        app.source_list.select(index);
+
+        let source = match app.source_list.get(index) {
+            Some(source) => source,
+            None => return,
+        };
+
+        let change = match button {
+            MouseButton::Left => match volume_area {
+                Some(area) if area.contains(position) => VolumeChange::Set(crate::draw::volume_at(area, position.x, app.max_volume)),
+                _ => return,
+            },
+            MouseButton::WheelUp   => VolumeChange::Increase(app.config.volume_step_small),
+            MouseButton::WheelDown => VolumeChange::Decrease(app.config.volume_step_small),
+            _ => return,
+        };
+        let new_vol = app.source_view_data.channels.change_volume(&source.volume, change, app.max_volume);
+        backend.set_source_volume(source.index, &new_vol);
+    }
+}